@@ -0,0 +1,139 @@
+// Benchmarks for the simulation: a frame of `Traffic::update` at different numbers of cars and sizes of network, making the textures, and drawing a whole frame. Drawing is done by SDL's software renderer in a hidden window on the dummy video driver, so no screen is needed. Run with `cargo bench --bench simulation`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use frontend::{
+    camera::Camera,
+    sim::{self, Overlays},
+    textures,
+};
+use sdl2::{render::Canvas, video::Window, Sdl};
+use smart_road::{
+    cars::Traffic, geometry::Geometry, network::Network, random, types::Dimensions, world,
+};
+
+const WINDOW: (i32, i32) = (860, 860);
+
+// The usual world, but with `columns` by `columns` crossroads.
+fn junctions(columns: usize) -> Dimensions {
+    let mut dimensions = world::dimensions(WINDOW.0, WINDOW.1);
+    dimensions.network = Network::grid(columns, columns, WINDOW.0, WINDOW.1, || {
+        Geometry::new([3, 3, 3, 3])
+    });
+    dimensions
+}
+
+// Runs the simulation, spawning a car every frame, until there are `cars` cars about or it has gone on long enough.
+fn traffic(cars: usize, dimensions: &Dimensions) -> Traffic {
+    // The same cars every time, so that timings can be compared between runs.
+    random::seed(1);
+    let mut traffic = Traffic::new();
+    for _ in 0..20 * cars {
+        if traffic.cars.len() >= cars {
+            break;
+        }
+        traffic.push_random(dimensions);
+        traffic.update(dimensions);
+    }
+    traffic
+}
+
+// A hidden window drawn into in memory.
+fn offscreen() -> (Sdl, Canvas<Window>) {
+    std::env::set_var("SDL_VIDEODRIVER", "dummy");
+    let sdl_context = sdl2::init().unwrap();
+    let window = sdl_context
+        .video()
+        .unwrap()
+        .window("Smart Road", WINDOW.0 as u32, WINDOW.1 as u32)
+        .hidden()
+        .build()
+        .unwrap();
+    let canvas = window.into_canvas().software().build().unwrap();
+    (sdl_context, canvas)
+}
+
+// The same number of cars is denser on one junction than spread over a grid of them.
+fn update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+    group.sample_size(20);
+    for columns in [1, 3] {
+        let dimensions = junctions(columns);
+        for cars in [50, 200, 1000] {
+            let mut traffic = traffic(cars, &dimensions);
+            // A single junction fills up at a few hundred cars, so each is timed at the number it got to.
+            let cars = traffic.cars.len();
+            group.throughput(Throughput::Elements(traffic.cars.len() as u64));
+            group.bench_with_input(
+                BenchmarkId::new(format!("{0}x{0} junctions", columns), cars),
+                &cars,
+                |b, &cars| {
+                    b.iter(|| {
+                        // Keep the numbers up as cars leave.
+                        if traffic.cars.len() < cars {
+                            traffic.push_random(&dimensions);
+                        }
+                        traffic.update(&dimensions);
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn textures(c: &mut Criterion) {
+    let (_sdl_context, mut canvas) = offscreen();
+    let texture_creator = canvas.texture_creator();
+    let dimensions = junctions(1);
+    let mut group = c.benchmark_group("textures");
+    group.sample_size(20);
+    group.bench_function("create_textures", |b| {
+        b.iter(|| textures::create_textures(&texture_creator, &dimensions, &mut canvas))
+    });
+    group.bench_function("create_speckled_texture", |b| {
+        b.iter(|| {
+            textures::create_speckled_texture(
+                &texture_creator,
+                WINDOW.0 as u32,
+                WINDOW.1 as u32,
+                &mut canvas,
+            )
+        })
+    });
+    group.finish();
+}
+
+fn render(c: &mut Criterion) {
+    let (_sdl_context, mut canvas) = offscreen();
+    let texture_creator = canvas.texture_creator();
+    let mut group = c.benchmark_group("render");
+    group.sample_size(20);
+    for cars in [0, 200] {
+        let dimensions = junctions(1);
+        let traffic = traffic(cars, &dimensions);
+        let (background_texture, lanes_texture, car_textures, tree_textures) =
+            textures::create_textures(&texture_creator, &dimensions, &mut canvas);
+        let camera = Camera::fit(&dimensions);
+        group.bench_with_input(BenchmarkId::new("frame", cars), &cars, |b, _| {
+            b.iter(|| {
+                sim::render(
+                    &mut canvas,
+                    &dimensions,
+                    &camera,
+                    &traffic,
+                    &background_texture,
+                    &car_textures,
+                    &lanes_texture,
+                    &texture_creator,
+                    &tree_textures,
+                    60.0,
+                    &Overlays::default(),
+                )
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, update, textures, render);
+criterion_main!(benches);
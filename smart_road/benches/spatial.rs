@@ -0,0 +1,74 @@
+// Times finding the cars each car could bump into, by looking at every other car and by looking them up in a `Grid`, for more and more cars at the same density. Run with `cargo bench -p smart_road --bench spatial`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::Rng;
+use smart_road::grid::Grid;
+
+const LANE_WIDTH: i32 = 16;
+// Room for each car, in square pixels: about as crowded as a busy junction.
+const AREA_PER_CAR: i32 = 40 * 40;
+
+fn spatial(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spatial");
+    for cars in [100usize, 250, 500, 1000, 2500, 5000, 10000] {
+        let side = ((cars as i32 * AREA_PER_CAR) as f64).sqrt() as i32;
+        let mut rng = rand::thread_rng();
+        let positions = (0..cars)
+            .map(|_| (rng.gen_range(0..side), rng.gen_range(0..side)))
+            .collect::<Vec<(i32, i32)>>();
+        assert_eq!(all_pairs(&positions), with_grid(&positions));
+
+        group.bench_with_input(
+            BenchmarkId::new("all pairs", cars),
+            &positions,
+            |b, positions| b.iter(|| all_pairs(positions)),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("grid", cars),
+            &positions,
+            |b, positions| b.iter(|| with_grid(positions)),
+        );
+    }
+    group.finish();
+}
+
+fn overlap(a: (i32, i32), b: (i32, i32)) -> bool {
+    a.0 < b.0 + LANE_WIDTH
+        && a.0 + LANE_WIDTH > b.0
+        && a.1 < b.1 + LANE_WIDTH
+        && a.1 + LANE_WIDTH > b.1
+}
+
+fn all_pairs(positions: &[(i32, i32)]) -> usize {
+    positions
+        .iter()
+        .enumerate()
+        .filter(|&(i, &a)| {
+            positions
+                .iter()
+                .enumerate()
+                .any(|(j, &b)| i != j && overlap(a, b))
+        })
+        .count()
+}
+
+// Builds the grid afresh, as `Traffic::update` does every frame.
+fn with_grid(positions: &[(i32, i32)]) -> usize {
+    let grid = Grid::new(2 * LANE_WIDTH, positions.to_vec());
+    positions
+        .iter()
+        .enumerate()
+        .filter(|&(i, &(x, y))| {
+            grid.near(
+                x - LANE_WIDTH,
+                y - LANE_WIDTH,
+                x + LANE_WIDTH,
+                y + LANE_WIDTH,
+            )
+            .any(|(j, other_x, other_y)| i != j && overlap((x, y), (other_x, other_y)))
+        })
+        .count()
+}
+
+criterion_group!(benches, spatial);
+criterion_main!(benches);
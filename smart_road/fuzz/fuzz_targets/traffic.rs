@@ -0,0 +1,81 @@
+#![no_main]
+
+// Reads the input as commands for `Traffic`, checking the invariants on every frame. The first byte picks the share of connected cars and the weather, the second the junctions, and the third and fourth the radio, as `layout` and `channel` read them. After that, a byte below 0x80 runs 1 to 128 frames, and any other byte spawns a car from the side in its low two bits, with its route picked from the rest. Collisions, cars leaving their paths and panics all crash, and so does any car still on the road long after the last spawn.
+
+use libfuzzer_sys::fuzz_target;
+use smart_road::{
+    cars::Traffic, geometry::Geometry, invariants::Invariants, network::Network, random,
+    types::Airt, v2i::ChannelConfig, weather::Weather, world,
+};
+
+// Frames cars must be apart for, the same as the property tests, so that the load stays moderate and the known gridlocks under overload don't count as deadlocks.
+const SPACING: u64 = 30;
+// Frames after the last command for every car to get out.
+const DRAIN: u64 = 6000;
+
+// The junctions picked by a byte: a crossroad or a roundabout with one or two lanes going round, by the byte modulo 3; no arm missing or the one on a side, by the byte over 3 modulo 5; and one junction or two by two, by the byte over 15 modulo 2.
+fn layout(byte: u8) -> Network {
+    let columns = 1 + (byte / 15 % 2) as usize;
+    Network::grid(columns, columns, 860, 860, || {
+        let mut geometry = match byte % 3 {
+            0 => Geometry::new([3, 3, 3, 3]),
+            circulating => Geometry::roundabout([3, 3, 3, 3], circulating as usize),
+        };
+        if let Some(&side) = Airt::ALL.get((byte / 3 % 5) as usize) {
+            geometry.remove_arm(side);
+        }
+        geometry
+    })
+}
+
+// The radio picked by two bytes: frames of delay from the low four bits of the first and frames of jitter from the high four, and the chance of losing a message from the second, over 1000.
+fn channel(timing: u8, loss: u8) -> ChannelConfig {
+    ChannelConfig {
+        delay: (timing & 0x0f) as u64,
+        jitter: (timing >> 4) as u64,
+        loss: loss as f64 / 1000.0,
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let &[first, junctions, timing, loss, ref commands @ ..] = data else {
+        return;
+    };
+    let mut dimensions = world::dimensions(860, 860);
+    dimensions.penetration = [0.0, 0.5, 1.0][(first % 3) as usize];
+    dimensions.weather = [Weather::Dry, Weather::Wet, Weather::Snow, Weather::Fog][(first / 3 % 4) as usize];
+    dimensions.network = layout(junctions);
+    dimensions.channel = channel(timing, loss);
+
+    let mut traffic = Traffic::new();
+    let mut invariants = Invariants::new(true);
+    invariants.stuck = u64::MAX;
+    traffic.invariants = Some(invariants);
+    random::seed(first as u64);
+
+    let mut last = None;
+    for (i, &command) in commands.iter().enumerate() {
+        if command < 0x80 {
+            for _ in 0..=command {
+                traffic.update(&dimensions);
+            }
+        } else if last.is_none_or(|last| traffic.frame >= last + SPACING) {
+            last = Some(traffic.frame);
+            random::seed(((command >> 2) as u64) << 32 | i as u64);
+            traffic.push(Airt::ALL[(command & 3) as usize], &dimensions);
+        }
+    }
+    for _ in 0..DRAIN {
+        if traffic.cars.is_empty() {
+            break;
+        }
+        traffic.update(&dimensions);
+    }
+    assert!(
+        traffic.cars.is_empty(),
+        "Deadlock: {} cars still on the road {} frames after the last command\n{}",
+        traffic.cars.len(),
+        DRAIN,
+        traffic.dump()
+    );
+});
@@ -0,0 +1,378 @@
+// Runs experiments without a window: `headless sweep ...`, `replicate ...`, `compare ...`, `events ...`, `timeline ...` and `gym ...`. Each command's arguments are described above its function below; the simulation they run is in `experiment` and `env`, and everything printed is printed here.
+
+use std::{fs, io, thread, time::Instant};
+
+use rand::Rng;
+use smart_road::{
+    cars::Traffic,
+    env::{Config, Env, REWARD},
+    events::Log,
+    experiment::{self, arrive, Outcome, Policy, Settings},
+    metrics::{Series, EVERY},
+    random,
+    statistics::{welch, Summary},
+    weather::Weather,
+};
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command: fn(&[String]) -> Result<(), String> = match args.first().map(String::as_str) {
+        Some("sweep") => sweep,
+        Some("replicate") => replicate,
+        Some("compare") => compare,
+        Some("events") => events,
+        Some("timeline") => timeline,
+        Some("gym") => gym,
+        _ => {
+            eprintln!("Usage: headless sweep|replicate|compare|events|timeline|gym key=value ...");
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = command(&args[1..]) {
+        eprintln!("{}", error);
+        std::process::exit(2);
+    }
+}
+
+// The values to try for each parameter, from arguments like `rate=0.5,1,2` and `seed=1..11`. Parameters left out keep one default value.
+struct Sweep {
+    rates: Vec<f64>,
+    speeds: Vec<f64>,
+    policies: Vec<Policy>,
+    weathers: Vec<Weather>,
+    seeds: Vec<u64>,
+    frames: u64,
+    check: bool,
+    threads: usize,
+    out: String, // Where the results table is written
+}
+
+impl Sweep {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut sweep = Sweep {
+            rates: vec![1.0],
+            speeds: vec![1.0],
+            policies: vec![Policy::Reservation],
+            weathers: vec![Weather::Dry],
+            seeds: vec![1],
+            frames: 18_000,
+            check: false,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+            out: "sweep.csv".to_string(),
+        };
+        for arg in args {
+            let (key, values) = arg
+                .split_once('=')
+                .ok_or_else(|| format!("Expected key=values, got '{}'", arg))?;
+            match key {
+                "rate" => sweep.rates = list(values, number)?,
+                "speed" => sweep.speeds = list(values, number)?,
+                "policy" => sweep.policies = list(values, Policy::parse)?,
+                "weather" => sweep.weathers = list(values, weather)?,
+                "seed" => sweep.seeds = seeds(values)?,
+                "runs" => sweep.seeds = (1..=number(values)?).collect(),
+                "frames" => sweep.frames = number(values)?,
+                "check" => sweep.check = number::<u8>(values)? != 0,
+                "threads" => sweep.threads = number(values)?,
+                "out" => sweep.out = values.to_string(),
+                _ => return Err(format!("Unknown parameter '{}'", key)),
+            }
+        }
+        // Every other list has at least one value, but `runs=0` or a range such as `5..5` leaves no seeds, and with them nothing to run or average.
+        if sweep.seeds.is_empty() {
+            return Err(
+                "No seeds to run: give at least one run, or a range whose end is after its start"
+                    .to_string(),
+            );
+        }
+        Ok(sweep)
+    }
+
+    // Every combination of the values.
+    fn settings(&self) -> Vec<Settings> {
+        let mut settings = Vec::new();
+        for &rate in &self.rates {
+            for &speed in &self.speeds {
+                for &policy in &self.policies {
+                    for &weather in &self.weathers {
+                        for &seed in &self.seeds {
+                            settings.push(Settings {
+                                rate,
+                                speed,
+                                policy,
+                                weather,
+                                seed,
+                                frames: self.frames,
+                                check: self.check,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        settings
+    }
+}
+
+fn number<T: std::str::FromStr>(text: &str) -> Result<T, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("'{}' isn't a number", text))
+}
+
+fn list<T>(values: &str, parse: impl Fn(&str) -> Result<T, String>) -> Result<Vec<T>, String> {
+    values.split(',').map(|value| parse(value.trim())).collect()
+}
+
+fn weather(name: &str) -> Result<Weather, String> {
+    let mut weather = Weather::Dry;
+    for _ in 0..4 {
+        if format!("{:?}", weather).eq_ignore_ascii_case(name) {
+            return Ok(weather);
+        }
+        weather = weather.next();
+    }
+    Err(format!(
+        "Unknown weather '{}': use dry, wet, snow or fog",
+        name
+    ))
+}
+
+// A list of seeds, or a range of them such as `1..11`, which leaves out the end.
+fn seeds(values: &str) -> Result<Vec<u64>, String> {
+    match values.split_once("..") {
+        Some((start, end)) => Ok((number(start)?..number(end)?).collect()),
+        None => list(values, number),
+    }
+}
+
+// Runs every settings as `experiment::run_all` does, showing how far it has got and the first violation of each run that had any.
+fn run_all(settings: &[Settings], threads: usize) -> Vec<Outcome> {
+    eprintln!("{} runs on {} threads", settings.len(), threads);
+    let outcomes = experiment::run_all(settings, threads, |done, outcome| {
+        if let Some(violation) = &outcome.violation {
+            eprintln!(
+                "\rSeed {}, {}: {}",
+                outcome.settings.seed,
+                outcome.settings.describe(),
+                violation
+            );
+        }
+        eprint!("\r{}/{} runs", done, settings.len());
+    });
+    eprintln!();
+    outcomes
+}
+
+fn write(out: &str, outcomes: &[Outcome]) -> Result<(), String> {
+    let mut table = Outcome::HEADER.to_string();
+    for outcome in outcomes {
+        table.push('\n');
+        table.push_str(&outcome.row());
+    }
+    table.push('\n');
+    fs::write(out, table).map_err(|error| format!("Couldn't write {}: {}", out, error))?;
+    eprintln!("Wrote {}", out);
+    Ok(())
+}
+
+// Each measure over a configuration's replications.
+fn summaries(outcomes: &[Outcome]) -> Vec<Summary> {
+    (0..Outcome::MEASURES.len())
+        .map(|measure| {
+            let samples: Vec<f64> = outcomes
+                .iter()
+                .map(|outcome| outcome.measures()[measure])
+                .collect();
+            Summary::of(&samples)
+        })
+        .collect()
+}
+
+// `headless sweep rate=0.5,1,2 policy=reservation,gaps seed=1..6` runs every combination of the given values without a window and writes a table of what each run gave.
+fn sweep(args: &[String]) -> Result<(), String> {
+    let sweep = Sweep::parse(args)?;
+    let outcomes = run_all(&sweep.settings(), sweep.threads);
+    write(&sweep.out, &outcomes)
+}
+
+// `headless replicate rate=2 policy=reservation,gaps runs=30` runs each combination with every seed, as `sweep` does, then gives the mean of each measure over the seeds with its 95% confidence interval.
+fn replicate(args: &[String]) -> Result<(), String> {
+    let sweep = Sweep::parse(args)?;
+    // A confidence interval needs the spread between runs, so there have to be at least two.
+    if sweep.seeds.len() < 2 {
+        return Err("Replication needs at least 2 runs".to_string());
+    }
+    let outcomes = run_all(&sweep.settings(), sweep.threads);
+    write(&sweep.out, &outcomes)?;
+
+    // The seeds are the innermost loop of `Sweep::settings`, so each configuration's runs come together.
+    for replications in outcomes.chunks(sweep.seeds.len()) {
+        println!(
+            "\n{} ({} runs)",
+            replications[0].settings.describe(),
+            replications.len()
+        );
+        for (name, summary) in Outcome::MEASURES.iter().zip(summaries(replications)) {
+            let margin = summary.margin();
+            println!(
+                "  {:<12} {:>10.3} ± {:<10.3} 95% CI {:.3} to {:.3}",
+                name,
+                summary.mean,
+                margin,
+                summary.mean - margin,
+                summary.mean + margin
+            );
+        }
+    }
+    Ok(())
+}
+
+// `headless compare rate=2 runs=30 vs policy=gaps` runs the configuration before `vs`, and the same with the changes after it, over the same seeds, and tests whether each measure differs between them with Welch's t-test.
+fn compare(args: &[String]) -> Result<(), String> {
+    let split = args
+        .iter()
+        .position(|arg| arg == "vs")
+        .ok_or("Expected the first configuration, then vs, then what the second changes")?;
+    let first = Sweep::parse(&args[..split])?;
+    // Later values override earlier ones, so the second is the first with its own changes.
+    let mut second_args = args[..split].to_vec();
+    second_args.extend_from_slice(&args[split + 1..]);
+    let second = Sweep::parse(&second_args)?;
+
+    let (a, b) = (first.settings(), second.settings());
+    if a.len() != first.seeds.len() || b.len() != second.seeds.len() {
+        return Err("Each side of a comparison takes one value of each parameter".to_string());
+    }
+    if first.seeds.len() < 2 || second.seeds.len() < 2 {
+        return Err("A comparison needs at least 2 runs on each side".to_string());
+    }
+    let mut outcomes = run_all(&[a, b].concat(), first.threads);
+    let second_outcomes = outcomes.split_off(first.seeds.len());
+
+    println!("\nA: {}", outcomes[0].settings.describe());
+    println!("B: {}", second_outcomes[0].settings.describe());
+    println!(
+        "\n  {:<12} {:>10} {:>10} {:>10} {:>8} {:>8}",
+        "", "A", "B", "B - A", "t", "p"
+    );
+    for ((name, a), b) in Outcome::MEASURES
+        .iter()
+        .zip(summaries(&outcomes))
+        .zip(summaries(&second_outcomes))
+    {
+        let (t, p) = welch(&b, &a);
+        println!(
+            "  {:<12} {:>10.3} {:>10.3} {:>10.3} {:>8.2} {:>8.4}{}",
+            name,
+            a.mean,
+            b.mean,
+            b.mean - a.mean,
+            t,
+            p,
+            if p < 0.05 { "  differs at 5%" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+// `headless events rate=2 seed=3 frames=600` runs one configuration and prints each event as it happens, a line each after its frame.
+fn events(args: &[String]) -> Result<(), String> {
+    let settings = Sweep::parse(args)?.settings();
+    let [settings] = settings[..] else {
+        return Err("Events are logged for one value of each parameter".to_string());
+    };
+    random::seed(settings.seed);
+    let dimensions = settings.dimensions();
+    let mut traffic = Traffic::new();
+    traffic.subscribe(Log(io::stdout().lock()));
+    for _ in 0..settings.frames {
+        arrive(&mut traffic, settings.rate, &dimensions);
+        traffic.update(&dimensions);
+    }
+    Ok(())
+}
+
+// `headless timeline rate=2 seed=3 every=60 out=timeline.csv` runs one configuration, sampling it every `every` frames, and writes the samples to a table.
+fn timeline(args: &[String]) -> Result<(), String> {
+    let mut every = EVERY;
+    let mut out = "timeline.csv".to_string();
+    let mut rest = Vec::new();
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("every", value)) => every = number(value)?,
+            Some(("out", value)) => out = value.to_string(),
+            _ => rest.push(arg.clone()),
+        }
+    }
+    let settings = Sweep::parse(&rest)?.settings();
+    let [settings] = settings[..] else {
+        return Err("The timeline takes one value of each parameter".to_string());
+    };
+    random::seed(settings.seed);
+    let dimensions = settings.dimensions();
+    let mut traffic = Traffic::new();
+    traffic.series = Some(Series::new(every));
+    for _ in 0..settings.frames {
+        arrive(&mut traffic, settings.rate, &dimensions);
+        traffic.update(&dimensions);
+    }
+    let series = traffic.series.unwrap();
+    fs::write(&out, series.csv()).map_err(|error| format!("Couldn't write {}: {}", out, error))?;
+    println!("Wrote {} samples to {}", series.samples.len(), out);
+    Ok(())
+}
+
+// `headless gym steps=100000 every=10 rate=2` drives the environment with a controller that acts at random, as a baseline and to see how many steps a second it manages. `every` is frames per step, and the rest configure the traffic as for `sweep`, with one value each.
+fn gym(args: &[String]) -> Result<(), String> {
+    let mut steps: u64 = 100_000;
+    let mut frames_per_step: u64 = 10;
+    let mut rest = Vec::new();
+    for arg in args {
+        match arg.split_once('=') {
+            Some(("steps", value)) => steps = number(value)?,
+            Some(("every", value)) => frames_per_step = number(value)?,
+            _ => rest.push(arg.clone()),
+        }
+    }
+    let settings = Sweep::parse(&rest)?.settings();
+    let [settings] = settings[..] else {
+        return Err("The environment takes one value of each parameter".to_string());
+    };
+
+    let mut env = Env::new(Config::new(settings, frames_per_step, REWARD)?);
+    let junctions = env.shape()[0];
+    let start = Instant::now();
+    let mut episodes = Vec::new();
+    let mut total = 0.0;
+    env.reset(settings.seed);
+    for step in 0..steps {
+        let action: Vec<u8> = (0..junctions)
+            .map(|_| random::rng().gen_range(0..16))
+            .collect();
+        let outcome = env.step(&action);
+        total += outcome.reward;
+        if outcome.done {
+            episodes.push(total);
+            total = 0.0;
+            env.reset(settings.seed + step + 1);
+        }
+    }
+    let seconds = start.elapsed().as_secs_f64();
+    println!(
+        "{} steps of {} frames in {:.1}s, {:.0} steps a second",
+        steps,
+        frames_per_step,
+        seconds,
+        steps as f64 / seconds
+    );
+    println!("Observations are {:?}", env.shape());
+    if !episodes.is_empty() {
+        println!(
+            "Mean reward over {} episodes acting at random: {:.2}",
+            episodes.len(),
+            episodes.iter().sum::<f64>() / episodes.len() as f64
+        );
+    }
+    Ok(())
+}
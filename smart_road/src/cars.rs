@@ -0,0 +1,1273 @@
+use rand::Rng;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    drivers::Driver,
+    events::{Event, Subscriber},
+    geometry::{point, Kind},
+    grid::Grid,
+    heatmap::Heatmap,
+    intersection::{Advice, Intersection, Request},
+    invariants::Invariants,
+    metrics::Series,
+    network::{Crossing, Leg},
+    path::Path,
+    random,
+    types::{Airt, Dimensions, Turn},
+    v2i::{Channel, ChannelConfig, Message},
+    weather::Weather,
+};
+
+// A radio that always gets messages through on the next frame, until `Traffic::update` picks up the configured one.
+const PERFECT: ChannelConfig = ChannelConfig {
+    delay: 0,
+    jitter: 0,
+    loss: 0.0,
+};
+
+// Counts kept apart for connected cars and for cars driven by people.
+#[derive(Default)]
+pub struct Tally {
+    pub passed: i32,
+    pub give_ways: i32,
+    pub passed_without_stopping: i32,
+    pub time: Duration, // Summed over the cars that passed
+    pub frames: u64,    // Frames from spawning to leaving, summed over the cars that passed
+    pub delay: f64,     // Frames lost against driving the whole way at cruising speed, summed over the cars that passed
+}
+
+impl Tally {
+    // Counts an event of one of the cars the tally is kept for.
+    fn count(&mut self, event: &Event) {
+        match *event {
+            Event::Yielded { .. } => self.give_ways += 1,
+            Event::Exited {
+                frames,
+                time,
+                delay,
+                stopped,
+                ..
+            } => {
+                self.passed += 1;
+                self.time += time;
+                self.frames += frames;
+                self.delay += delay;
+                if !stopped {
+                    self.passed_without_stopping += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn format(&self) -> String {
+        if self.passed == 0 {
+            return "0 passed".to_string();
+        }
+        format!(
+            "{} passed, {} give ways, {} without stopping, {:.2}s on average",
+            self.passed,
+            self.give_ways,
+            self.passed_without_stopping,
+            self.time.as_secs_f64() / self.passed as f64
+        )
+    }
+}
+
+pub struct Traffic {
+    pub cars: Vec<Car>,
+    pub cars_passed: i32,
+    pub give_ways: i32,
+    pub max_time: Duration,
+    pub min_time: Duration,
+    pub max_velocity: i32,
+    pub min_velocity: i32,
+    pub close_calls: i32,
+    pub collisions: i32,
+    pub lane_changes: i32,
+    pub refused: i32, // Cars that couldn't be spawned, as a queue reached back to the edge
+    pub passed_without_stopping: i32,
+    pub connected: Tally,
+    pub human: Tally,
+    pub by_profile: Vec<(&'static str, Tally)>, // One for each driver profile, by name
+    pub frame: u64,
+    pub intersections: Vec<Intersection>, // One for each junction in the network
+    pub uplink: Channel,   // Messages from cars to junctions
+    pub downlink: Channel, // Messages from junctions to cars
+    pub invariants: Option<Invariants>, // Checks run after every frame when set
+    pub series: Option<Series>,         // Samples taken every few frames when set
+    pub heatmap: Option<Heatmap>,       // Where cars are held up and close calls happen, when set
+    pub events: Vec<(u64, Event)>, // What happened on the last frame, and to cars spawned since the frame before, with the frames they happened on
+    pending: Vec<(u64, Event)>, // Events waiting for the end of the frame
+    subscribers: Vec<Box<dyn Subscriber>>,
+    next_id: u64,
+    weather: Weather, // The weather the cars were last set up for
+}
+
+impl Default for Traffic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Traffic {
+    pub fn new() -> Self {
+        Traffic {
+            cars: Vec::new(),
+            cars_passed: 0,
+            give_ways: 0,
+            max_time: Duration::from_millis(0),
+            min_time: Duration::MAX,
+            max_velocity: 0,
+            min_velocity: i32::MAX,
+            close_calls: 0,
+            collisions: 0,
+            lane_changes: 0,
+            refused: 0,
+            passed_without_stopping: 0,
+            connected: Tally::default(),
+            human: Tally::default(),
+            by_profile: Vec::new(),
+            frame: 0,
+            intersections: Vec::new(),
+            uplink: Channel::new(PERFECT),
+            downlink: Channel::new(PERFECT),
+            invariants: None,
+            series: None,
+            heatmap: None,
+            events: Vec::new(),
+            pending: Vec::new(),
+            subscribers: Vec::new(),
+            next_id: 0,
+            weather: Weather::Dry,
+        }
+    }
+
+
+    pub fn format(&self) -> String {
+        if self.cars_passed == 0 {
+            "=== SMART ROAD STATISTICS ===\n\n\
+             Cars passed: 0\n\
+             Give ways: 0\n\
+             Close calls: 0\n\
+             Lane changes: 0\n\
+             Passed without stopping: 0\n\n\
+             By Type:\n\
+             • Connected: 0 passed\n\
+             • Human-driven: 0 passed\n\n\
+             Velocity Stats:\n\
+             • Max velocity: N/A\n\
+             • Min velocity: N/A\n\n\
+             Time Stats:\n\
+             • Max time: N/A\n\
+             • Min time: N/A\n\n\
+             Status: No data collected yet".to_string()
+        } else {
+            let min_vel = if self.min_velocity == i32::MAX {
+                "N/A".to_string()
+            } else {
+                format!("{}px/s", self.min_velocity)
+            };
+            
+            format!(
+                "=== SMART ROAD STATISTICS ===\n\n\
+                 Traffic Summary:\n\
+                 • Cars passed: {}\n\
+                 • Give ways: {}\n\
+                 • Close calls: {}\n\
+                 • Lane changes: {}\n\
+                 • Passed without stopping: {}\n\
+                 • Spawns refused, the edge being queued up to: {}\n\n\
+                 By Type:\n\
+                 • Connected: {}\n\
+                 • Human-driven: {}\n\n\
+                 By Profile:\n\
+                 {}\n\n\
+                 Velocity Stats:\n\
+                 • Max velocity: {}px/s\n\
+                 • Min velocity: {}\n\n\
+                 Time Stats:\n\
+                 • Max time: {:.2}s\n\
+                 • Min time: {:.2}s\n\n\
+                 Safety Rating: {}",
+                self.cars_passed,
+                self.give_ways,
+                self.close_calls,
+                self.lane_changes,
+                self.passed_without_stopping,
+                self.refused,
+                self.connected.format(),
+                self.human.format(),
+                self.by_profile
+                    .iter()
+                    .map(|(name, tally)| format!("• {}: {}", name, tally.format()))
+                    .collect::<Vec<String>>()
+                    .join("\n"),
+                self.max_velocity,
+                min_vel,
+                self.max_time.as_secs_f64(),
+                self.min_time.as_secs_f64(),
+                if self.close_calls == 0 {
+                    "✓ EXCELLENT (No close calls)"
+                } else if self.close_calls < 5 {
+                    "⚠ GOOD (Few close calls)"
+                } else {
+                    "✗ NEEDS IMPROVEMENT (Many close calls)"
+                }
+            )
+        }
+    }
+
+    // Makes the intersection logic for each junction, unless it is made already. Every update does this, so it is only needed to get at the intersections before the first frame.
+    pub fn build(&mut self, dimensions: &Dimensions) {
+        let junctions = &dimensions.network.junctions;
+        if self.intersections.len() != junctions.len() {
+            self.intersections = junctions.iter().map(|_| Intersection::new()).collect();
+        }
+    }
+
+    pub fn push(&mut self, initial_direction: Airt, dimensions: &Dimensions) {
+        if let Some(mut car) = Car::spawn(initial_direction, self.next_id, dimensions) {
+            // A queue reaching back to the edge leaves no room for another car to drive in. The car is counted as refused rather than spawned on top of the last in the queue.
+            let lane_width = dimensions.lane_width;
+            if self.cars.iter().any(|other| {
+                (car.x - other.x).abs() < lane_width && (car.y - other.y).abs() < lane_width
+            }) {
+                self.pending.push((
+                    self.frame,
+                    Event::Refused {
+                        from: initial_direction,
+                    },
+                ));
+                return;
+            }
+            self.pending.push((
+                self.frame,
+                Event::Spawned {
+                    car: car.id,
+                    from: initial_direction,
+                },
+            ));
+            self.next_id += 1;
+            car.born = self.frame;
+            self.cars.push(car);
+        }
+    }
+
+    pub fn push_random(&mut self, dimensions: &Dimensions) {
+        // Only directions that some junction on the edge has an approach for.
+        let directions: Vec<Airt> = Airt::ALL
+            .into_iter()
+            .filter(|&direction| !dimensions.network.entries(direction).is_empty())
+            .collect();
+        if directions.is_empty() {
+            return;
+        }
+        let random_direction = directions[random::rng().gen_range(0..directions.len())];
+        self.push(random_direction, dimensions);
+    }
+
+    pub fn update(&mut self, dimensions: &Dimensions) {
+        // Where each car will be once it has moved, by its place in `cars` this frame, on a grid of cells two lanes wide so that a car only has to be checked against those nearby.
+        let mut prospective_positions = Grid::new(
+            2 * dimensions.lane_width,
+            self.cars.iter().map(|car| (car.x, car.y)).collect(),
+        );
+
+        // Cars on each roundabout, which cars waiting to enter it give way to, with what held them up on the last frame. A car at the line only looks at the ring it is joining.
+        let mut circulating = dimensions
+            .network
+            .junctions
+            .iter()
+            .map(|_| Vec::new())
+            .collect::<Vec<Vec<Circulating>>>();
+        for car in &self.cars {
+            if let Some(crossing) = car.crossing() {
+                if let Kind::Roundabout { .. } = dimensions.network.junctions[crossing.junction].geometry.kind {
+                    circulating[crossing.junction].push((car.x, car.y, car.id, car.wait));
+                }
+            }
+        }
+
+        if self.weather != dimensions.weather {
+            self.weather = dimensions.weather;
+            for car in self.cars.iter_mut() {
+                car.adapt(dimensions);
+            }
+        }
+
+        if self.by_profile.len() != dimensions.profiles.len() {
+            self.by_profile = dimensions
+                .profiles
+                .iter()
+                .map(|profile| (profile.name, Tally::default()))
+                .collect();
+        }
+
+        // The intersection logic of each junction advises the cars approaching it, talking to them over the simulated radio.
+        self.frame += 1;
+        self.build(dimensions);
+        let junctions = &dimensions.network.junctions;
+        for intersection in self.intersections.iter_mut() {
+            intersection.margin = dimensions.weather.margin();
+            intersection.tick(self.frame);
+        }
+        // Arrival times have to leave room for the answer to get to the car before it is due at the junction.
+        let lead = dimensions.channel.worst_delay() + dimensions.weather.margin() + 1;
+        // Junctions sense cars that aren't connected on the crossing or due at it within that lead, and keep their cells for them, so that grants go round them. A person looking out for a gap can't see a car whose grant is still on its way to it, so they have to be sensed before they could drive on in front of it.
+        for car in self.cars.iter().filter(|car| !car.connected) {
+            if let Some((junction, request)) = car.sensed(self.frame, lead) {
+                self.intersections[junction].sense(
+                    car.id,
+                    &request,
+                    junctions[junction].centre,
+                    dimensions.lane_width,
+                );
+            }
+        }
+        // Junctions make a gap for people who have waited at the line too long, until they are through.
+        let mut waiting = junctions.iter().map(|_| Vec::new()).collect::<Vec<Vec<(u64, Airt)>>>();
+        let mut crossing = vec![false; junctions.len()];
+        for car in self.cars.iter().filter(|car| !car.connected) {
+            if let Some((junction, _)) = car.side() {
+                crossing[junction] = true;
+            } else if let Some((junction, start, _, _)) = car.approach() {
+                if !car.held && car.wait == Some(Wait::Junction) {
+                    waiting[junction].push((car.id, start));
+                }
+            }
+        }
+        for (intersection, (waiting, crossing)) in
+            self.intersections.iter_mut().zip(waiting.into_iter().zip(crossing))
+        {
+            intersection.wait(waiting, crossing, self.frame);
+        }
+        self.uplink.config = dimensions.channel;
+        self.downlink.config = dimensions.channel;
+        let earliest = self.frame + lead;
+        for message in self.uplink.receive(self.frame) {
+            let junction = message.junction();
+            if let Some(reply) = self.intersections[junction].answer(
+                message,
+                junctions[junction].centre,
+                earliest,
+                dimensions.lane_width,
+            ) {
+                self.downlink.send(self.frame, reply);
+            }
+        }
+        let slots = self
+            .cars
+            .iter()
+            .enumerate()
+            .map(|(slot, car)| (car.id, slot))
+            .collect::<HashMap<u64, usize>>();
+        for message in self.downlink.receive(self.frame) {
+            if let Some(&slot) = slots.get(&message.car()) {
+                self.cars[slot].hear(message);
+            }
+        }
+        // Cars in a queue ask in turn from the front, so that no car is given a slot it can't reach for the car in front of it. This is how far the nearest car in each lane that still has to ask is from its junction.
+        let mut unasked: HashMap<(usize, Airt, usize), f64> = HashMap::new();
+        for car in self.cars.iter().filter(|car| car.grant.is_none()) {
+            if let Some((junction, start, lane, left)) = car.approach() {
+                let nearest = unasked.entry((junction, start, lane)).or_insert(left);
+                *nearest = nearest.min(left);
+            }
+        }
+        for car in self.cars.iter_mut() {
+            car.held = car.approach().is_some_and(|(junction, start, _, _)| {
+                self.intersections[junction].held[start.index()]
+            });
+            let first = car.approach().is_none_or(|(junction, start, lane, left)| {
+                unasked
+                    .get(&(junction, start, lane))
+                    .is_none_or(|&nearest| nearest >= left)
+            });
+            car.talk(&mut self.uplink, first, self.frame, dimensions);
+        }
+
+        // Drivers of cars that aren't connected look out for a gap themselves. Once one of them goes, the others see a car on the junction. What they look for is the soonest a car they can see on its way from each side would get there, worked out once for every driver. A car held up with a slot on the junction is still on its way, and due when its slot comes.
+        let sight = dimensions.weather.sight() * dimensions.lane_width as f64;
+        let mut soonest = vec![[f64::INFINITY; 4]; junctions.len()];
+        for car in &self.cars {
+            let Some((junction, side, _, ahead)) = car.approach() else {
+                continue;
+            };
+            if ahead > sight {
+                continue;
+            }
+            let due = match car.grant {
+                Some((crossing, advice)) if car.halted && crossing.junction == junction => {
+                    advice.arrival.saturating_sub(self.frame) as f64
+                }
+                _ if !car.halted && car.speed > 0 => ahead / car.speed as f64,
+                _ => continue,
+            };
+            let frames = &mut soonest[junction][side.index()];
+            *frames = frames.min(due);
+        }
+        let mut sides = junctions.iter().map(|_| Vec::new()).collect::<Vec<Vec<Airt>>>();
+        for car in &self.cars {
+            if let Some((junction, side)) = car.side() {
+                sides[junction].push(side);
+            }
+        }
+        for car in self.cars.iter_mut().filter(|car| !car.connected) {
+            car.look(&soonest, &mut sides, dimensions);
+        }
+
+        // Check for close calls before updating positions
+        let mut events = Vec::new();
+        self.check_close_calls(&prospective_positions, &mut events, dimensions);
+
+        let ids = self.cars.iter().map(|car| car.id).collect::<Vec<u64>>();
+        for (slot, car) in self.cars.iter_mut().enumerate() {
+            car.halted = !car.update(
+                slot,
+                &mut prospective_positions,
+                &ids,
+                &circulating,
+                &mut events,
+                self.frame,
+                dimensions,
+            );
+            if car.halted {
+                events.push(Event::Yielded {
+                    car: car.id,
+                    connected: car.connected,
+                    profile: car.driver.profile,
+                });
+                if !car.stopped {
+                    events.push(Event::Stopped { car: car.id });
+                }
+                car.stopped = true;
+            }
+        }
+
+        self.cars.retain(|car| !car.gone);
+
+        let frame = self.frame;
+        self.pending.extend(events.into_iter().map(|event| (frame, event)));
+        self.publish();
+
+        if let Some(mut series) = self.series.take() {
+            series.record(self);
+            self.series = Some(series);
+        }
+        if let Some(mut heatmap) = self.heatmap.take() {
+            heatmap.record(self);
+            self.heatmap = Some(heatmap);
+        }
+
+        if let Some(mut invariants) = self.invariants.take() {
+            invariants.check(self, dimensions);
+            self.invariants = Some(invariants);
+        }
+    }
+
+    // Adds a subscriber that hears about every event from now on, at the end of the frame it happened on.
+    pub fn subscribe(&mut self, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push(Box::new(subscriber));
+    }
+
+    // Counts the frame's events towards the statistics, then passes them on to the subscribers. Everything counted is counted here, so the totals and the tallies by kind of car and by profile always agree.
+    fn publish(&mut self) {
+        for &(_, event) in &self.pending {
+            match event {
+                Event::LaneChanged { .. } => self.lane_changes += 1,
+                Event::Refused { .. } => self.refused += 1,
+                Event::Yielded { .. } => self.give_ways += 1,
+                Event::CloseCall { .. } => self.close_calls += 1,
+                Event::Collision { .. } => self.collisions += 1,
+                Event::Exited {
+                    time,
+                    stopped,
+                    fastest,
+                    slowest,
+                    ..
+                } => {
+                    self.cars_passed += 1;
+                    if !stopped {
+                        self.passed_without_stopping += 1;
+                    }
+                    self.max_time = self.max_time.max(time);
+                    self.min_time = self.min_time.min(time);
+                    self.max_velocity = self.max_velocity.max(fastest);
+                    self.min_velocity = self.min_velocity.min(slowest);
+                }
+                Event::Spawned { .. }
+                | Event::EnteredJunction { .. }
+                | Event::Stopped { .. } => {}
+            }
+            if let Event::Yielded {
+                connected, profile, ..
+            }
+            | Event::Exited {
+                connected, profile, ..
+            } = event
+            {
+                let kind = if connected {
+                    &mut self.connected
+                } else {
+                    &mut self.human
+                };
+                kind.count(&event);
+                self.by_profile[profile].1.count(&event);
+            }
+        }
+        for subscriber in self.subscribers.iter_mut() {
+            for (frame, event) in &self.pending {
+                subscriber.notify(*frame, event);
+            }
+        }
+        self.events = std::mem::take(&mut self.pending);
+    }
+
+    // The state of every car, one per line, for reporting what went wrong.
+    pub fn dump(&self) -> String {
+        let mut dump = format!("{} cars on frame {}:\n", self.cars.len(), self.frame);
+        for car in &self.cars {
+            dump += &car.dump();
+            dump.push('\n');
+        }
+        dump
+    }
+
+    // Each car on its way to a junction, as the junction's sensors see it.
+    pub fn approaching(&self) -> impl Iterator<Item = Sighting> + '_ {
+        self.cars
+            .iter()
+            .filter_map(|car| Some((car.approach()?, car.speed, car.halted)))
+    }
+
+    // Cars closer than the safety distance are close calls, and cars whose footprints overlap have collided.
+    fn check_close_calls(&self, positions: &Grid, events: &mut Vec<Event>, dimensions: &Dimensions) {
+        let lane_width = dimensions.lane_width;
+        let safety_distance = (dimensions.lane_width as f32 * 1.5) as i32;
+        for i in 0..self.cars.len() {
+            let (x, y) = positions.position(i);
+            let near = positions.near(
+                x - safety_distance,
+                y - safety_distance,
+                x + safety_distance,
+                y + safety_distance,
+            );
+            for (j, other_x, other_y) in near {
+                if j <= i {
+                    continue;
+                }
+
+                let dx = x - other_x;
+                let dy = y - other_y;
+                let distance = ((dx * dx + dy * dy) as f32).sqrt() as i32;
+                
+                let cars = (self.cars[i].id, self.cars[j].id);
+                if distance < safety_distance && distance > lane_width {
+                    events.push(Event::CloseCall { cars });
+                }
+                if dx.abs() < lane_width && dy.abs() < lane_width {
+                    events.push(Event::Collision { cars });
+                }
+            }
+        }
+    }
+}
+
+// Why a car was held up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Wait {
+    LaneChange,     // Waiting for a gap to get into the lane for its turn
+    Junction,       // Waiting for its turn at the junction: for advice it can use, for a controller to let it go, or, for people, for a gap
+    Yielding(u64),  // Giving way to this car on a roundabout
+    Behind(u64),    // Would run into this car
+}
+
+// A car on a roundabout, as drivers waiting to go on see it: where the car is, its id and what held it up on the last frame.
+type Circulating = (i32, i32, u64, Option<Wait>);
+
+// What a driver can see of another car: the approach it is on, as given by `Car::approach`, its speed, and whether it is held up.
+pub type Sighting = ((usize, Airt, usize, f64), i32, bool);
+
+pub struct Car {
+    pub x: i32,
+    pub y: i32,
+    pub color_code: usize,
+    pub speed: i32,
+    pub target_speed: i32, // For smooth acceleration/deceleration
+    pub current_angle: f64,  // Current rotation angle for smooth turning
+    target_angle: f64,   // Target rotation angle
+    gone: bool,
+    birthday: Instant,
+    born: u64, // The frame the car was spawned on
+    pub path: Path,
+    pub travelled: f64, // Distance covered along `path`
+    crossings: Vec<Crossing>,
+    legs: Vec<Leg>, // The junctions `crossings` go through, in the same order
+    changing: f64,  // Distance along `path` where the lane change in progress ends
+    waiting: u32,   // Frames spent waiting for a gap to change lanes
+    cruise: i32,    // The speed the driver goes at when not advised otherwise
+    pub id: u64,    // Given in the order cars are spawned and never changed, so it names the car in messages, logs and overlays
+    grant: Option<(Crossing, Advice)>, // Advice granted for the junction the car is approaching or crossing
+    asked: Option<u64>, // The sequence number of the request still waiting for an answer
+    retry: u64,     // The frame from which the car may ask again
+    stopped: bool,  // Whether the car has ever had to stop
+    fastest: i32,   // The fastest and slowest the car has gone
+    slowest: i32,
+    pub halted: bool, // Whether the car was held up on the last frame
+    pub wait: Option<Wait>, // What held it up
+    held: bool,     // Whether a controller is keeping the car's approach back
+    pub connected: bool, // Connected cars talk to junctions; the others are driven by people
+    driver: Driver,
+    clear: u32,     // Frames the driver has seen a gap for
+}
+
+impl Car {
+    // Spawns a car driving into the world heading in `initial_direction` at a random junction on the edge, with a random route across the network. There is nowhere to spawn if no junction has an approach from that side.
+    pub fn spawn(initial_direction: Airt, id: u64, dimensions: &Dimensions) -> Option<Self> {
+        let network = &dimensions.network;
+        let entries = network.entries(initial_direction);
+        if entries.is_empty() {
+            return None;
+        }
+        let junction = entries[random::rng().gen_range(0..entries.len())];
+        let legs = network.route(junction, initial_direction)?;
+
+        let driver = Driver::pick(&dimensions.profiles);
+        let speed = Car::turn_speed(legs[0].turn, &driver, dimensions);
+        let connected = random::rng().gen_bool(dimensions.penetration.clamp(0.0, 1.0));
+        let color_code = match initial_direction {
+            Airt::Up => 0,
+            Airt::Down => 1,
+            Airt::Right => 2,
+            Airt::Left => 3,
+        };
+
+        let (path, crossings) = network.path(&legs, dimensions.lane_width);
+        let (x, y) = path.position(0.0);
+        let initial_angle = initial_direction.angle();
+
+        Some(Car {
+            x: x.round() as i32,
+            y: y.round() as i32,
+            color_code,
+            speed,
+            target_speed: speed,
+            current_angle: initial_angle,
+            target_angle: initial_angle,
+            gone: false,
+            birthday: Instant::now(),
+            born: 0,
+            path,
+            travelled: 0.0,
+            crossings,
+            legs,
+            changing: 0.0,
+            waiting: 0,
+            cruise: speed,
+            id,
+            grant: None,
+            asked: None,
+            retry: 0,
+            stopped: false,
+            fastest: speed,
+            slowest: speed,
+            halted: false,
+            wait: None,
+            held: false,
+            connected,
+            driver,
+            clear: 0,
+        })
+    }
+
+    // The speed the driver likes to go at for the turn in the weather. Nobody goes faster than `fast`, which keeps a car from skipping over another between frames.
+    fn turn_speed(turn: Turn, driver: &Driver, dimensions: &Dimensions) -> i32 {
+        let speed = match turn {
+            Turn::Left => dimensions.speed.default,
+            Turn::Straight => dimensions.speed.fast,
+            Turn::Right => dimensions.speed.slow,
+        };
+        let speed = speed as f64 * driver.speed * dimensions.weather.speed();
+        (speed.round() as i32).clamp(1, dimensions.speed.fast)
+    }
+
+    // The place on the grid of a car this one would run into at (`new_x`, `new_y`). `slot` is the car's own place, which it doesn't collide with.
+    fn will_collide(
+        &self,
+        slot: usize,
+        new_x: i32,
+        new_y: i32,
+        prospective_positions: &Grid,
+        dimensions: &Dimensions,
+    ) -> Option<usize> {
+        let lane_width = dimensions.lane_width;
+        prospective_positions
+            .near(
+                new_x - lane_width,
+                new_y - lane_width,
+                new_x + lane_width,
+                new_y + lane_width,
+            )
+            .find(|&(other, x, y)| {
+                other != slot
+                    && new_x < x + lane_width
+                    && new_x + lane_width > x
+                    && new_y < y + lane_width
+                    && new_y + lane_width > y
+            })
+            .map(|(other, _, _)| other)
+    }
+
+    // The junction the car is driving through, if it's in one.
+    pub fn crossing(&self) -> Option<&Crossing> {
+        self.crossings
+            .iter()
+            .find(|crossing| crossing.entry <= self.travelled && self.travelled < crossing.exit)
+    }
+
+    // The car on the ring close to where this one would join, if it is at the give-way line of a roundabout. A car on the ring that is held up behind this one is let by, as it can't move until this one does.
+    fn must_yield(&self, circulating: &[Vec<Circulating>], dimensions: &Dimensions) -> Option<u64> {
+        let next = self.travelled + self.speed as f64;
+        let crossing = self
+            .crossings
+            .iter()
+            .find(|crossing| self.travelled < crossing.entry && crossing.entry <= next)?;
+
+        let (x, y) = self.path.position(crossing.entry);
+        let gap = 3.0 * dimensions.lane_width as f64;
+        circulating[crossing.junction]
+            .iter()
+            .find(|&&(other_x, other_y, id, wait)| {
+                let (dx, dy) = (other_x as f64 - x, other_y as f64 - y);
+                id != self.id
+                    && wait != Some(Wait::Behind(self.id))
+                    && (dx * dx + dy * dy).sqrt() < gap
+            })
+            .map(|&(_, _, id, _)| id)
+    }
+
+    // Index into `legs` of the next junction the car will enter.
+    fn next_leg(&self) -> Option<usize> {
+        self.crossings
+            .iter()
+            .position(|crossing| self.travelled < crossing.entry)
+    }
+
+    // The lane next to the car's, towards one its next turn can be made from, if it isn't in one already.
+    fn wanted_lane(&self, dimensions: &Dimensions) -> Option<usize> {
+        let leg = &self.legs[self.next_leg()?];
+        let target = dimensions.network.turning_lane(leg, leg.lane);
+        if target > leg.lane {
+            Some(leg.lane + 1)
+        } else if target < leg.lane {
+            Some(leg.lane - 1)
+        } else {
+            None
+        }
+    }
+
+    // Whether the car is still where new cars drive in, which would put a new car on top of it mid lane change.
+    fn near_edge(&self, dimensions: &Dimensions) -> bool {
+        let margin = 2 * dimensions.lane_width;
+        self.x < margin
+            || self.y < margin
+            || self.x + margin + dimensions.lane_width > dimensions.network.width
+            || self.y + margin + dimensions.lane_width > dimensions.network.height
+    }
+
+    // How far ahead a lane change reaches.
+    fn change_length(dimensions: &Dimensions) -> i32 {
+        3 * dimensions.lane_width
+    }
+
+    // Whether `lane` is clear from a couple of car lengths behind the car to just past where the lane change would end.
+    fn gap_free(
+        &self,
+        slot: usize,
+        lane: usize,
+        prospective_positions: &Grid,
+        dimensions: &Dimensions,
+    ) -> bool {
+        let leg = &self.legs[self.next_leg().unwrap()];
+        let heading = leg.start;
+        let lane_width = dimensions.lane_width;
+        let along = |x: i32, y: i32| if heading.is_vertical() { y } else { x };
+        let across = |x: i32, y: i32| if heading.is_vertical() { x } else { y };
+
+        let here = along(self.x, self.y);
+        let behind = here - heading.sign() * 2 * lane_width;
+        let ahead = here + heading.sign() * (Car::change_length(dimensions) + lane_width);
+        let (low, high) = (behind.min(ahead), behind.max(ahead) + lane_width);
+        // Also look half way into the lane beyond, where a car could be moving over into the same lane.
+        let side = dimensions
+            .network
+            .across(leg.junction, heading, lane, lane_width);
+        let (near, far) = if side > across(self.x, self.y) {
+            (side, side + lane_width + lane_width / 2)
+        } else {
+            (side - lane_width / 2, side + lane_width)
+        };
+
+        let (left, top, right, bottom) = if heading.is_vertical() {
+            (near - lane_width, low - lane_width, far, high)
+        } else {
+            (low - lane_width, near - lane_width, high, far)
+        };
+        !prospective_positions.near(left, top, right, bottom).any(|(other, x, y)| {
+            other != slot
+                && along(x, y) < high
+                && along(x, y) + lane_width > low
+                && across(x, y) < far
+                && across(x, y) + lane_width > near
+        })
+    }
+
+    // Moves the car over into `lane`, rebuilding its path from where it is now.
+    fn change_lane(&mut self, lane: usize, dimensions: &Dimensions) {
+        let network = &dimensions.network;
+        let next = self.next_leg().unwrap();
+        self.legs.drain(..next);
+        self.legs[0].lane = lane;
+        network.follow(&mut self.legs);
+
+        let heading = self.legs[0].start;
+        let (x, y) = self.path.position(self.travelled);
+        let here = if heading.is_vertical() { y } else { x }.round() as i32;
+        let end = point(
+            heading,
+            here + heading.sign() * Car::change_length(dimensions),
+            network.across(self.legs[0].junction, heading, lane, dimensions.lane_width),
+        );
+
+        let (path, crossings) =
+            network.path_from(vec![(x, y), end], &self.legs, dimensions.lane_width);
+        self.changing = path.distance(1);
+        self.path = path;
+        self.crossings = crossings;
+        self.travelled = 0.0;
+        self.waiting = 0;
+    }
+
+    // Takes up the speed the driver would like to go at in a change of weather, unless the car is following advice.
+    fn adapt(&mut self, dimensions: &Dimensions) {
+        self.cruise = Car::turn_speed(self.legs[0].turn, &self.driver, dimensions);
+        if self.grant.is_none() {
+            self.target_speed = self.cruise;
+        }
+    }
+
+    // Gives up on changing lanes and takes a turn the car's lane allows instead, finding a new way from there.
+    fn take_other_turn(&mut self, dimensions: &Dimensions) {
+        let network = &dimensions.network;
+        let next = self.next_leg().unwrap();
+        let leg = self.legs[next];
+        let Some(legs) = network.reroute(leg.junction, leg.start, leg.lane) else {
+            return;
+        };
+
+        let (x, y) = self.path.position(self.travelled);
+        let (path, crossings) = network.path_from(vec![(x, y)], &legs, dimensions.lane_width);
+        self.cruise = Car::turn_speed(legs[0].turn, &self.driver, dimensions);
+        self.target_speed = self.cruise;
+        self.legs = legs;
+        self.path = path;
+        self.crossings = crossings;
+        self.travelled = 0.0;
+        self.changing = 0.0;
+        self.waiting = 0;
+    }
+
+    // The way the car is pointing along its path, in degrees.
+    pub fn heading(&self) -> f64 {
+        self.path.heading(self.travelled)
+    }
+
+    // Everything about the car on one line, for `Traffic::dump`.
+    fn dump(&self) -> String {
+        let legs = self
+            .legs
+            .iter()
+            .map(|leg| format!("{}:{:?}/{}/{:?}", leg.junction, leg.start, leg.lane, leg.turn))
+            .collect::<Vec<String>>()
+            .join(" ");
+        let grant = match &self.grant {
+            Some((crossing, advice)) => format!(
+                "junction {} at {} speed {}",
+                crossing.junction, advice.arrival, advice.speed
+            ),
+            None => "none".to_string(),
+        };
+        format!(
+            "car {}: at ({}, {}) speed {} of {} travelled {:.1} of {:.1} legs [{}] grant {} connected {} halted {} wait {:?} held {} born {}",
+            self.id,
+            self.x,
+            self.y,
+            self.speed,
+            self.target_speed,
+            self.travelled,
+            self.path.length(),
+            legs,
+            grant,
+            self.connected,
+            self.halted,
+            self.wait,
+            self.held,
+            self.born
+        )
+    }
+
+    // The junction the car is heading for, the side and lane it is coming in on, and how far it has left to go to get there.
+    fn approach(&self) -> Option<(usize, Airt, usize, f64)> {
+        let next = self.next_leg()?;
+        let leg = &self.legs[next];
+        Some((leg.junction, leg.start, leg.lane, self.crossings[next].entry - self.travelled))
+    }
+
+    // The junction the car is driving through and the side it came in from.
+    fn side(&self) -> Option<(usize, Airt)> {
+        let crossing = self.crossing()?;
+        let leg = self.legs.iter().find(|leg| leg.junction == crossing.junction)?;
+        Some((crossing.junction, leg.start))
+    }
+
+    // The car's way through the junction it is on or would drive onto within `lead` frames at the speed it is going, as the junction's sensors see it. A car held up isn't on its way, and is only sensed once it is at the line.
+    fn sensed(&self, frame: u64, lead: u64) -> Option<(usize, Request)> {
+        let lead = if self.halted { 0 } else { lead };
+        let next = self.travelled + (self.speed as u64 * (lead + 1) + 1) as f64;
+        let crossing = self
+            .crossings
+            .iter()
+            .find(|crossing| crossing.entry <= next && self.travelled < crossing.exit)?;
+        let leg = self.legs.iter().find(|leg| leg.junction == crossing.junction)?;
+        Some((
+            crossing.junction,
+            Request {
+                path: self.path.clone(),
+                start: leg.start,
+                entry: crossing.entry.max(self.travelled),
+                exit: crossing.exit,
+                travelled: self.travelled,
+                speed: self.speed,
+                frame,
+            },
+        ))
+    }
+
+    // Watches for a gap from the driver's accepted gap out from the junction, or as far as they can see: no car from another side on it, and none they can see from another side on its way that would get there within that many frames. Drivers leave bigger gaps when there is less grip. Cars held up at their own line aren't on their way, unless they have a slot. At a roundabout the driver just gives way to the ring.
+    fn look(
+        &mut self,
+        soonest: &[[f64; 4]], // For each junction, the frames until the first car seen on its way from each side gets there
+        sides: &mut [Vec<Airt>],
+        dimensions: &Dimensions,
+    ) {
+        let Some((junction, start, _, left)) = self.approach() else {
+            self.clear = 0;
+            return;
+        };
+        if let Kind::Roundabout { .. } = dimensions.network.junctions[junction].geometry.kind {
+            self.clear = self.driver.reaction;
+            return;
+        }
+        let accepted = self.driver.gap / dimensions.weather.grip();
+        let sight = dimensions.weather.sight() * dimensions.lane_width as f64;
+        let coming = Airt::ALL
+            .into_iter()
+            .any(|side| side != start && soonest[junction][side.index()] < accepted);
+        let gap = !coming && sides[junction].iter().all(|&side| side == start);
+        if gap && left <= (accepted * self.speed.max(1) as f64).min(sight) {
+            self.clear += 1;
+        } else {
+            self.clear = 0;
+        }
+        if self.clear >= self.driver.reaction && left <= (self.speed + 1) as f64 {
+            sides[junction].push(start);
+        }
+    }
+
+    // Asks the junction ahead when to arrive, once the car is in the lane for its turn, at the front of the cars there still waiting to ask, and close enough for the advice to be worth following, and goes back to its own speed once through. A car with a grant keeps the junction posted on where it is. Requests that go unanswered, for instance because a message was lost, are made again.
+    fn talk(&mut self, uplink: &mut Channel, first: bool, frame: u64, dimensions: &Dimensions) {
+        if !self.connected {
+            return;
+        }
+        if let Some((crossing, advice)) = self.grant {
+            uplink.send(
+                frame,
+                Message::Beacon {
+                    car: self.id,
+                    junction: crossing.junction,
+                    past_exit: self.travelled - crossing.exit,
+                },
+            );
+            if self.travelled >= crossing.exit {
+                self.grant = None;
+                self.target_speed = self.cruise;
+            } else if self.travelled < crossing.entry {
+                if frame > advice.arrival + dimensions.weather.margin() {
+                    // Held up too long to use the slot, so ask for another.
+                    self.grant = None;
+                    self.target_speed = self.cruise;
+                } else {
+                    // Keep aiming for the advised frame, making up for any hold-ups on the way.
+                    let frames = advice.arrival.saturating_sub(frame).max(1) as f64;
+                    let speed = ((crossing.entry - self.travelled) / frames).ceil() as i32;
+                    self.target_speed = speed.clamp(1, self.cruise);
+                    return;
+                }
+            } else {
+                self.target_speed = advice.speed;
+                return;
+            }
+        }
+
+        let Some(next) = self.next_leg() else {
+            return;
+        };
+        let crossing = self.crossings[next];
+        if self.crossing().is_some()
+            || !first
+            || self.held
+            || self.wanted_lane(dimensions).is_some()
+            || crossing.entry - self.travelled > 12.0 * dimensions.lane_width as f64
+            || frame < self.retry
+        {
+            return;
+        }
+
+        uplink.send(
+            frame,
+            Message::Request {
+                car: self.id,
+                junction: crossing.junction,
+                sequence: frame,
+                request: Request {
+                    path: self.path.clone(),
+                    start: self.legs[next].start,
+                    entry: crossing.entry,
+                    exit: crossing.exit,
+                    travelled: self.travelled,
+                    speed: self.cruise,
+                    frame,
+                },
+            },
+        );
+        self.asked = Some(frame);
+        self.retry = frame + Car::timeout(&dimensions.channel);
+    }
+
+    // How long to wait for an answer before asking again.
+    fn timeout(channel: &ChannelConfig) -> u64 {
+        2 * channel.worst_delay() + 2
+    }
+
+    // Takes in an answer from a junction. Answers to anything but the latest request are old news and ignored.
+    fn hear(&mut self, message: Message) {
+        match message {
+            Message::Grant {
+                junction,
+                sequence,
+                advice,
+                ..
+            } if self.asked == Some(sequence) => {
+                self.asked = None;
+                let Some(next) = self.next_leg() else {
+                    return;
+                };
+                let crossing = self.crossings[next];
+                if crossing.junction == junction && self.crossing().is_none() {
+                    self.target_speed = advice.speed;
+                    self.grant = Some((crossing, advice));
+                }
+            }
+            Message::Deny { sequence, .. } if self.asked == Some(sequence) => {
+                // Try again once the timeout is up.
+                self.asked = None;
+            }
+            _ => {}
+        }
+    }
+
+    // Whether the car may drive into the junction it is about to enter: only within the window of a grant for it, or for a car driven by a person, once the driver has seen a gap for long enough.
+    fn may_enter(&self, frame: u64, dimensions: &Dimensions) -> bool {
+        let next = self.travelled + self.speed as f64;
+        let Some(crossing) = self
+            .crossings
+            .iter()
+            .find(|crossing| self.travelled < crossing.entry && crossing.entry <= next)
+        else {
+            return true;
+        };
+        if self.held {
+            return false;
+        }
+        if !self.connected {
+            return self.clear >= self.driver.reaction;
+        }
+        match self.grant {
+            Some((granted, advice)) => {
+                granted.entry == crossing.entry
+                    && frame + dimensions.weather.margin() >= advice.arrival
+                    && frame <= advice.arrival + dimensions.weather.margin()
+            }
+            None => false,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update(
+        &mut self,
+        slot: usize, // Where the car is on `prospective_positions`
+        prospective_positions: &mut Grid,
+        ids: &[u64], // The id of the car in each slot
+        circulating: &[Vec<Circulating>], // For each junction, the cars going round it
+        events: &mut Vec<Event>,
+        frame: u64,
+        dimensions: &Dimensions,
+    ) -> bool {
+        self.fastest = self.fastest.max(self.speed);
+        self.slowest = self.slowest.min(self.speed);
+        if self.travelled >= self.path.length() {
+            self.gone = true;
+            let frames = frame - self.born;
+            events.push(Event::Exited {
+                car: self.id,
+                frames,
+                time: Instant::now().duration_since(self.birthday),
+                delay: (frames as f64 - self.path.length() / self.cruise as f64).max(0.0),
+                stopped: self.stopped,
+                fastest: self.fastest,
+                slowest: self.slowest,
+                connected: self.connected,
+                profile: self.driver.profile,
+            });
+            return true;
+        }
+        self.wait = None;
+
+        // Change lanes on the approach when the lane next door has a big enough gap, one lane at a time.
+        if let Some(lane) = self.wanted_lane(dimensions) {
+            if self.travelled >= self.changing
+                && self.crossing().is_none()
+                && !self.near_edge(dimensions)
+                && self.gap_free(slot, lane, prospective_positions, dimensions)
+            {
+                self.change_lane(lane, dimensions);
+                events.push(Event::LaneChanged { car: self.id, lane });
+            }
+        }
+
+        // A car that still isn't in the right lane waits far enough back from the junction to change lanes once there is a gap.
+        if self.wanted_lane(dimensions).is_some() {
+            let entry = self.crossings[self.next_leg().unwrap()].entry;
+            let wait = entry - Car::change_length(dimensions) as f64;
+            if self.travelled + self.speed as f64 > wait {
+                // Two cars side by side that each want the other's lane would wait for ever, so after a while the driver settles for another way.
+                self.waiting += 1;
+                if self.waiting < 120 {
+                    self.wait = Some(Wait::LaneChange);
+                    return false;
+                }
+                self.take_other_turn(dimensions);
+            }
+        }
+
+        let (new_x, new_y) = self.calculate_new_position(dimensions);
+
+        // With less grip than on a dry road, the car needs more room ahead to stop in.
+        let (room_x, room_y) = self
+            .path
+            .position(self.travelled + self.speed as f64 + self.stopping_room(dimensions));
+
+        self.wait = if !self.may_enter(frame, dimensions) {
+            Some(Wait::Junction)
+        } else if let Some(other) = self.must_yield(circulating, dimensions) {
+            Some(Wait::Yielding(other))
+        } else {
+            self.will_collide(slot, new_x, new_y, prospective_positions, dimensions)
+                .or_else(|| {
+                    self.will_collide(
+                        slot,
+                        room_x.round() as i32,
+                        room_y.round() as i32,
+                        prospective_positions,
+                        dimensions,
+                    )
+                })
+                .map(|other| Wait::Behind(ids[other]))
+        };
+        if self.wait.is_some() {
+            return false;
+        }
+
+        prospective_positions.set(slot, new_x, new_y);
+
+        let entered = self.crossings.iter().find(|crossing| {
+            self.travelled < crossing.entry && crossing.entry <= self.travelled + self.speed as f64
+        });
+        if let Some(crossing) = entered {
+            events.push(Event::EnteredJunction {
+                car: self.id,
+                junction: crossing.junction,
+            });
+        }
+
+        self.x = new_x;
+        self.y = new_y;
+        self.travelled += self.speed as f64;
+
+        true
+    }
+
+
+    // How much further than on a dry road the car takes to stop from its speed.
+    fn stopping_room(&self, dimensions: &Dimensions) -> f64 {
+        let dry = (self.speed * self.speed) as f64 / (2 * self.driver.braking) as f64;
+        dry / dimensions.weather.grip() - dry
+    }
+
+    fn calculate_new_position(&mut self, dimensions: &Dimensions) -> (i32, i32) {
+        // Smooth speed transitions, as quick as the driver speeds up and brakes, braking less hard without grip
+        let braking = (self.driver.braking as f64 * dimensions.weather.grip()).round().max(1.0) as i32;
+        if self.speed < self.target_speed {
+            self.speed = (self.speed + self.driver.acceleration).min(self.target_speed);
+        } else if self.speed > self.target_speed {
+            self.speed = (self.speed - braking).max(self.target_speed);
+        }
+
+        // Smooth rotation interpolation
+        let rotation_speed = self.driver.rotation; // Degrees per frame
+        let angle_diff = self.target_angle - self.current_angle;
+        
+        // Handle angle wrapping (shortest path)
+        let normalized_diff = if angle_diff > 180.0 {
+            angle_diff - 360.0
+        } else if angle_diff < -180.0 {
+            angle_diff + 360.0
+        } else {
+            angle_diff
+        };
+        
+        if normalized_diff.abs() > rotation_speed {
+            self.current_angle += normalized_diff.signum() * rotation_speed;
+        } else {
+            self.current_angle = self.target_angle;
+        }
+        
+        // Normalize angle to 0-360 range
+        while self.current_angle < 0.0 {
+            self.current_angle += 360.0;
+        }
+        while self.current_angle >= 360.0 {
+            self.current_angle -= 360.0;
+        }
+
+        let distance = self.travelled + self.speed as f64;
+        let (new_x, new_y) = self.path.position(distance);
+        self.target_angle = self.path.heading(distance);
+
+        (new_x.round() as i32, new_y.round() as i32)
+    }
+}
@@ -0,0 +1,53 @@
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+
+use crate::random;
+
+// A kind of driver, such as a cautious one. Each driver's own values are picked evenly from these ranges, inclusive.
+#[derive(Clone, Copy)]
+pub struct Profile {
+    pub name: &'static str,
+    // How often drivers are of this kind, relative to the other kinds.
+    pub share: f64,
+    // The speed the driver likes to go at, as a multiple of the usual speed for their turn.
+    pub speed: (f64, f64),
+    // Frames the driver needs to have seen a gap before going. Only people driving cars that aren't connected wait for gaps.
+    pub reaction: (u32, u32),
+    // The shortest gap the driver will cross in front of, in frames before the other car gets to the junction.
+    pub gap: (f64, f64),
+    // Pixels per frame gained or lost each frame when speeding up and slowing down.
+    pub acceleration: (i32, i32),
+    pub braking: (i32, i32),
+    // Degrees per frame the car turns through.
+    pub rotation: (f64, f64),
+}
+
+// One driver's own values, picked from one of the profiles.
+#[derive(Clone, Copy)]
+pub struct Driver {
+    pub profile: usize, // Index into the profiles the driver was picked from
+    pub speed: f64,
+    pub reaction: u32,
+    pub gap: f64,
+    pub acceleration: i32,
+    pub braking: i32,
+    pub rotation: f64,
+}
+
+impl Driver {
+    pub fn pick(profiles: &[Profile]) -> Self {
+        let mut rng = random::rng();
+        let shares = WeightedIndex::new(profiles.iter().map(|profile| profile.share))
+            .expect("Driver profiles need shares that aren't all 0");
+        let index = shares.sample(&mut rng);
+        let profile = &profiles[index];
+        Driver {
+            profile: index,
+            speed: rng.gen_range(profile.speed.0..=profile.speed.1),
+            reaction: rng.gen_range(profile.reaction.0..=profile.reaction.1),
+            gap: rng.gen_range(profile.gap.0..=profile.gap.1),
+            acceleration: rng.gen_range(profile.acceleration.0..=profile.acceleration.1),
+            braking: rng.gen_range(profile.braking.0..=profile.braking.1),
+            rotation: rng.gen_range(profile.rotation.0..=profile.rotation.1),
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{
+    cars::Traffic,
+    experiment::{self, Settings},
+    random,
+    types::{Airt, Dimensions},
+};
+
+// Cars in each lane whose distance and speed are observed, nearest first.
+const NEAREST: usize = 4;
+// Numbers observed for each lane: the cars queuing, the cars on their way, then the distance in lane widths and the speed as a share of the fast speed of each of the nearest cars, with zeros where there are fewer.
+pub const FEATURES: usize = 2 + 2 * NEAREST;
+
+// What a step is rewarded for.
+#[derive(Clone, Copy)]
+pub struct Reward {
+    pub delay: f64,      // Taken off for each frame a car is held up
+    pub collision: f64,  // Taken off for each pair of cars whose footprints overlap
+    pub close_call: f64, // Taken off for each pair of cars closer than the safety distance without touching
+    pub passed: f64,     // Given for each car that gets through
+}
+
+pub const REWARD: Reward = Reward {
+    delay: 0.01,
+    collision: 10.0,
+    close_call: 1.0,
+    passed: 0.0,
+};
+
+pub struct Config {
+    settings: Settings, // The traffic to control. An episode lasts `settings.frames` frames
+    frames_per_step: u64, // Frames the simulation runs for between decisions
+    reward: Reward,
+}
+
+impl Config {
+    // A step has to run the simulation for at least a frame, or an episode would never end.
+    pub fn new(settings: Settings, frames_per_step: u64, reward: Reward) -> Result<Self, String> {
+        if frames_per_step == 0 {
+            return Err("A step has to be at least 1 frame".to_string());
+        }
+        Ok(Config {
+            settings,
+            frames_per_step,
+            reward,
+        })
+    }
+}
+
+// What `Env::step` gives back.
+pub struct Step {
+    pub observation: Vec<f32>,
+    pub reward: f64,
+    pub done: bool,
+}
+
+// The simulation as an environment for training junction controllers, in the style of Gym: reset, then observe and step until done. It runs without a window.
+pub struct Env {
+    config: Config,
+    dimensions: Dimensions,
+    pub traffic: Traffic,
+    lanes: usize, // The most lanes any approach has, which every approach is padded to
+}
+
+impl Env {
+    pub fn new(config: Config) -> Self {
+        let dimensions = config.settings.dimensions();
+        let lanes = dimensions
+            .network
+            .junctions
+            .iter()
+            .flat_map(|junction| junction.geometry.approaches.iter().map(Vec::len))
+            .max()
+            .unwrap_or(0);
+        let mut env = Env {
+            config,
+            dimensions,
+            traffic: Traffic::new(),
+            lanes,
+        };
+        env.traffic.build(&env.dimensions);
+        env
+    }
+
+    // How observations are laid out: by junction, then approach (by the way cars are heading, indexed with `Airt::index`), then lane, then `FEATURES` numbers.
+    pub fn shape(&self) -> [usize; 4] {
+        [
+            self.dimensions.network.junctions.len(),
+            Airt::ALL.len(),
+            self.lanes,
+            FEATURES,
+        ]
+    }
+
+    // Starts a new episode with empty roads, with the random numbers seeded from `seed`. The intersections are made straight away, so that the first step's action can hold cars back.
+    pub fn reset(&mut self, seed: u64) -> Vec<f32> {
+        random::seed(seed);
+        self.traffic = Traffic::new();
+        self.traffic.build(&self.dimensions);
+        self.observe()
+    }
+
+    pub fn observe(&self) -> Vec<f32> {
+        let [junctions, approaches, lanes, _] = self.shape();
+        let lane_width = self.dimensions.lane_width as f64;
+        let fast = self.dimensions.speed.fast as f32;
+        let mut cars: Vec<Vec<(f64, i32)>> = vec![Vec::new(); junctions * approaches * lanes];
+        let mut queues = vec![0.0; cars.len()];
+        for ((junction, start, lane, left), speed, halted) in self.traffic.approaching() {
+            let index = (junction * approaches + start.index()) * lanes + lane;
+            cars[index].push((left, speed));
+            if halted {
+                queues[index] += 1.0;
+            }
+        }
+
+        let mut observation = vec![0.0; cars.len() * FEATURES];
+        for (index, lane_cars) in cars.iter_mut().enumerate() {
+            lane_cars.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let features = &mut observation[index * FEATURES..(index + 1) * FEATURES];
+            features[0] = queues[index];
+            features[1] = lane_cars.len() as f32;
+            for (i, &(left, speed)) in lane_cars.iter().take(NEAREST).enumerate() {
+                features[2 + 2 * i] = (left / lane_width) as f32;
+                features[3 + 2 * i] = speed as f32 / fast;
+            }
+        }
+        observation
+    }
+
+    // Applies `action`, which has a number for each junction whose bits, by `Airt::index`, hold back the cars heading that way (so 0 leaves the junction to the reservations and drivers), then runs the simulation for a step.
+    pub fn step(&mut self, action: &[u8]) -> Step {
+        for (intersection, &bits) in self.traffic.intersections.iter_mut().zip(action) {
+            for side in Airt::ALL {
+                intersection.held[side.index()] = bits & (1 << side.index()) != 0;
+            }
+        }
+
+        let (give_ways, collisions, close_calls, passed) = (
+            self.traffic.give_ways,
+            self.traffic.collisions,
+            self.traffic.close_calls,
+            self.traffic.cars_passed,
+        );
+        for _ in 0..self.config.frames_per_step {
+            experiment::arrive(
+                &mut self.traffic,
+                self.config.settings.rate,
+                &self.dimensions,
+            );
+            self.traffic.update(&self.dimensions);
+        }
+        let reward = &self.config.reward;
+        Step {
+            observation: self.observe(),
+            reward: -reward.delay * (self.traffic.give_ways - give_ways) as f64
+                - reward.collision * (self.traffic.collisions - collisions) as f64
+                - reward.close_call * (self.traffic.close_calls - close_calls) as f64
+                + reward.passed * (self.traffic.cars_passed - passed) as f64,
+            done: self.traffic.frame >= self.config.settings.frames,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::{fmt, io::Write, time::Duration};
+
+use crate::types::Airt;
+
+// Something that happened on the road. Cars are named by their id.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Spawned {
+        car: u64,
+        from: Airt,
+    },
+    Refused {
+        from: Airt,
+    }, // No car could be spawned heading `from`, as a queue reached back to the edge
+    EnteredJunction {
+        car: u64,
+        junction: usize,
+    },
+    LaneChanged {
+        car: u64,
+        lane: usize,
+    },
+    // Held up for a frame, giving way or waiting for its turn. Whether the car is connected and its driver's profile, as an index into `Dimensions::profiles`, say which tallies it counts towards.
+    Yielded {
+        car: u64,
+        connected: bool,
+        profile: usize,
+    },
+    Stopped {
+        car: u64,
+    }, // Held up for the first time
+    CloseCall {
+        cars: (u64, u64),
+    },
+    Collision {
+        cars: (u64, u64),
+    },
+    // `frames` from spawning to leaving, `time` the same in wall-clock time, `delay` the frames lost against driving the whole way at cruising speed, whether the car ever had to stop and the fastest and slowest it went, with the tallies it counts towards as for `Yielded`.
+    Exited {
+        car: u64,
+        frames: u64,
+        time: Duration,
+        delay: f64,
+        stopped: bool,
+        fastest: i32,
+        slowest: i32,
+        connected: bool,
+        profile: usize,
+    },
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Spawned { car, from } => write!(f, "car {} spawned heading {:?}", car, from),
+            Event::Refused { from } => write!(f, "no room to spawn a car heading {:?}", from),
+            Event::EnteredJunction { car, junction } => {
+                write!(f, "car {} entered junction {}", car, junction)
+            }
+            Event::LaneChanged { car, lane } => write!(f, "car {} changed to lane {}", car, lane),
+            Event::Yielded { car, .. } => write!(f, "car {} yielded", car),
+            Event::Stopped { car } => write!(f, "car {} stopped", car),
+            Event::CloseCall { cars } => {
+                write!(f, "close call between cars {} and {}", cars.0, cars.1)
+            }
+            Event::Collision { cars } => {
+                write!(f, "collision between cars {} and {}", cars.0, cars.1)
+            }
+            Event::Exited { car, frames, .. } => {
+                write!(f, "car {} exited after {} frames", car, frames)
+            }
+        }
+    }
+}
+
+// Anything that wants to hear about events, given to `Traffic::subscribe`. Each event comes with the frame it happened on, once the frame is over.
+pub trait Subscriber {
+    fn notify(&mut self, frame: u64, event: &Event);
+}
+
+// A closure can subscribe, which is the easy way to share what it collects, through an `Rc<RefCell<_>>` or a channel.
+impl<F: FnMut(u64, &Event)> Subscriber for F {
+    fn notify(&mut self, frame: u64, event: &Event) {
+        self(frame, event)
+    }
+}
+
+// Writes each event on a line of its own after its frame. A log that can't be written to is no reason to stop the simulation, so errors are ignored.
+pub struct Log<W: Write>(pub W);
+
+impl<W: Write> Subscriber for Log<W> {
+    fn notify(&mut self, frame: u64, event: &Event) {
+        let _ = writeln!(self.0, "{} {}", frame, event);
+    }
+}
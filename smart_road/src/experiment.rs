@@ -0,0 +1,236 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use rand::Rng;
+
+use crate::{
+    cars::Traffic,
+    invariants::{Invariants, Violation},
+    metrics::FRAME,
+    random,
+    types::Dimensions,
+    weather::Weather,
+    world,
+};
+
+// The world experiments are run in, as if in a window this size.
+const WORLD: (i32, i32) = (860, 860);
+
+// How junctions decide who goes: by reservations with every car connected, by drivers waiting for gaps with none connected, or a mix with this share of cars connected.
+#[derive(Clone, Copy, Debug)]
+pub enum Policy {
+    Reservation,
+    Gaps,
+    Mixed(f64),
+}
+
+impl Policy {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "reservation" => Ok(Policy::Reservation),
+            "gaps" => Ok(Policy::Gaps),
+            _ => match text.parse::<f64>() {
+                Ok(share) if (0.0..=1.0).contains(&share) => Ok(Policy::Mixed(share)),
+                _ => Err(format!(
+                    "Unknown policy '{}': use reservation, gaps or a share of connected cars from 0 to 1",
+                    text
+                )),
+            },
+        }
+    }
+
+    fn penetration(self) -> f64 {
+        match self {
+            Policy::Reservation => 1.0,
+            Policy::Gaps => 0.0,
+            Policy::Mixed(share) => share,
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Policy::Reservation => "reservation".to_string(),
+            Policy::Gaps => "gaps".to_string(),
+            Policy::Mixed(share) => share.to_string(),
+        }
+    }
+}
+
+// One combination of parameters to run.
+#[derive(Clone, Copy, Debug)]
+pub struct Settings {
+    pub rate: f64,  // Cars arriving per second, on average
+    pub speed: f64, // A multiple of the usual speeds
+    pub policy: Policy,
+    pub weather: Weather,
+    pub seed: u64,
+    pub frames: u64,
+    pub check: bool, // Whether to check the invariants on every frame
+}
+
+impl Settings {
+    pub fn dimensions(&self) -> Dimensions {
+        let mut dimensions = world::dimensions(WORLD.0, WORLD.1);
+        let scale = |speed: i32| ((speed as f64 * self.speed).round() as i32).max(1);
+        dimensions.speed.fast = scale(dimensions.speed.fast);
+        dimensions.speed.default = scale(dimensions.speed.default);
+        dimensions.speed.slow = scale(dimensions.speed.slow);
+        dimensions.penetration = self.policy.penetration();
+        dimensions.weather = self.weather;
+        dimensions
+    }
+
+    // Everything but the seed, which replications differ in.
+    pub fn describe(&self) -> String {
+        format!(
+            "rate {}, speed {}, policy {}, weather {:?}, {} frames",
+            self.rate,
+            self.speed,
+            self.policy.name(),
+            self.weather,
+            self.frames
+        )
+    }
+}
+
+// The key numbers from a run.
+pub struct Outcome {
+    pub settings: Settings,
+    pub passed: i32,
+    pub throughput: f64, // Cars passed per minute
+    pub time: f64,       // Mean seconds from spawning to leaving
+    pub delay: f64,      // Mean seconds lost against driving the whole way at cruising speed
+    pub close_calls: i32,
+    pub stops: i32, // Cars that had to stop at least once
+    pub give_ways: i32,
+    pub remaining: usize,             // Cars still on the road at the end
+    pub refused: i32, // Cars that couldn't be spawned, as a queue reached back to the edge
+    pub violations: usize, // Frames the invariants didn't hold on, when checked
+    pub violation: Option<Violation>, // The first of them, as the ones after it tend to follow from it
+}
+
+impl Outcome {
+    pub const HEADER: &'static str = "rate,speed,policy,weather,seed,frames,passed,throughput,time,delay,close_calls,stops,give_ways,remaining,refused,violations";
+
+    // The measures that replications are summed up by.
+    pub const MEASURES: [&'static str; 4] = ["throughput", "delay", "close calls", "stops"];
+
+    pub fn measures(&self) -> [f64; 4] {
+        [
+            self.throughput,
+            self.delay,
+            self.close_calls as f64,
+            self.stops as f64,
+        ]
+    }
+
+    pub fn row(&self) -> String {
+        let settings = &self.settings;
+        format!(
+            "{},{},{},{:?},{},{},{},{:.2},{:.3},{:.3},{},{},{},{},{},{}",
+            settings.rate,
+            settings.speed,
+            settings.policy.name(),
+            settings.weather,
+            settings.seed,
+            settings.frames,
+            self.passed,
+            self.throughput,
+            self.time,
+            self.delay,
+            self.close_calls,
+            self.stops,
+            self.give_ways,
+            self.remaining,
+            self.refused,
+            self.violations
+        )
+    }
+}
+
+// Spawns the cars arriving on one frame, `rate` a second on average: whole cars each frame, and one more now and then for the fraction left over.
+pub fn arrive(traffic: &mut Traffic, rate: f64, dimensions: &Dimensions) {
+    let arrivals = rate * FRAME;
+    let mut cars = arrivals.floor() as usize;
+    if random::rng().gen_bool(arrivals.fract()) {
+        cars += 1;
+    }
+    for _ in 0..cars {
+        traffic.push_random(dimensions);
+    }
+}
+
+// Runs the simulation without a window, with cars arriving at random at `settings.rate`.
+pub fn run(settings: Settings) -> Outcome {
+    random::seed(settings.seed);
+    let dimensions = settings.dimensions();
+    let mut traffic = Traffic::new();
+    if settings.check {
+        traffic.invariants = Some(Invariants::new(false));
+    }
+    for _ in 0..settings.frames {
+        arrive(&mut traffic, settings.rate, &dimensions);
+        traffic.update(&dimensions);
+    }
+
+    let (violations, violation) = traffic.invariants.map_or((0, None), |invariants| {
+        (
+            invariants.violations.len(),
+            invariants.violations.into_iter().next(),
+        )
+    });
+    let passed = traffic.cars_passed;
+    let per_car = |frames: f64| {
+        if passed == 0 {
+            0.0
+        } else {
+            frames * FRAME / passed as f64
+        }
+    };
+    Outcome {
+        settings,
+        passed,
+        throughput: passed as f64 * 60.0 / (settings.frames as f64 * FRAME),
+        time: per_car((traffic.connected.frames + traffic.human.frames) as f64),
+        delay: per_car(traffic.connected.delay + traffic.human.delay),
+        close_calls: traffic.close_calls,
+        stops: passed - traffic.passed_without_stopping,
+        give_ways: traffic.give_ways,
+        remaining: traffic.cars.len(),
+        refused: traffic.refused,
+        violations,
+        violation,
+    }
+}
+
+// Runs every settings on a pool of `threads` threads, giving the outcomes in the same order. `progress` is told of each run as it finishes, with how many are done so far.
+pub fn run_all(
+    settings: &[Settings],
+    threads: usize,
+    progress: impl Fn(usize, &Outcome) + Sync,
+) -> Vec<Outcome> {
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let outcomes = Mutex::new(Vec::with_capacity(settings.len()));
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(&settings_here) = settings.get(index) else {
+                    break;
+                };
+                let outcome = run(settings_here);
+                progress(done.fetch_add(1, Ordering::Relaxed) + 1, &outcome);
+                outcomes.lock().unwrap().push((index, outcome));
+            });
+        }
+    });
+    let mut outcomes = outcomes.into_inner().unwrap();
+    outcomes.sort_by_key(|(index, _)| *index);
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
@@ -0,0 +1,298 @@
+use crate::types::{Airt, Turn};
+
+// The layout of a junction. Both the road drawing in `lanes.rs` and the paths cars follow are generated from it.
+pub struct Geometry {
+    pub kind: Kind,
+    // The lanes leading into the junction for each direction of travel (indexed with `Airt::index`), numbered from the centre line outwards.
+    pub approaches: [Vec<Lane>; 4],
+    // How many lanes lead out of the junction for each direction of travel.
+    pub exits: [usize; 4],
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Crossroad,
+    // A ring of `circulating` lanes around an island `island` lanes in radius. Traffic on the ring has priority over traffic entering it.
+    Roundabout { island: usize, circulating: usize },
+}
+
+pub struct Lane {
+    pub turns: Vec<Turn>,
+}
+
+impl Lane {
+    // The usual markings for an approach with `count` lanes: left turns from the lane nearest the centre line, right turns from the kerbside lane and straight on from everything in between.
+    pub fn split(count: usize) -> Vec<Lane> {
+        (0..count)
+            .map(|i| {
+                let turns = match (count, i) {
+                    (1, _) => vec![Turn::Left, Turn::Straight, Turn::Right],
+                    (2, 0) => vec![Turn::Left, Turn::Straight],
+                    (2, _) => vec![Turn::Straight, Turn::Right],
+                    (_, 0) => vec![Turn::Left],
+                    (_, i) if i == count - 1 => vec![Turn::Right],
+                    _ => vec![Turn::Straight],
+                };
+                Lane { turns }
+            })
+            .collect()
+    }
+}
+
+impl Geometry {
+    // A crossroad with `lanes[d]` lanes in and out for each direction of travel `d`, in `Airt::ALL` order.
+    pub fn new(lanes: [usize; 4]) -> Self {
+        Geometry {
+            kind: Kind::Crossroad,
+            approaches: lanes.map(Lane::split),
+            exits: lanes,
+        }
+    }
+
+    // A roundabout with the same arms as `new`, and `circulating` lanes around a central island.
+    pub fn roundabout(lanes: [usize; 4], circulating: usize) -> Self {
+        Geometry {
+            kind: Kind::Roundabout {
+                island: *lanes.iter().max().unwrap(),
+                circulating: circulating.max(1),
+            },
+            ..Geometry::new(lanes)
+        }
+    }
+
+    // Takes away the arm on the `side` of the junction, making a three-arm junction such as a T-junction. Movements that would lead into the missing arm are taken off the lane markings of the other arms.
+    pub fn remove_arm(&mut self, side: Airt) {
+        self.approaches[side.opposite().index()].clear();
+        self.exits[side.index()] = 0;
+
+        for direction in Airt::ALL {
+            let exits = self.exits;
+            let lanes = &mut self.approaches[direction.index()];
+            for lane in lanes.iter_mut() {
+                lane.turns
+                    .retain(|&turn| exits[direction.turn(turn).index()] > 0);
+            }
+            // A lane left without a movement takes on the movements of the nearest lane that still has one, preferring the lane nearer the centre line.
+            let before: Vec<Vec<Turn>> = lanes.iter().map(|lane| lane.turns.clone()).collect();
+            for (i, lane) in lanes.iter_mut().enumerate() {
+                if lane.turns.is_empty() {
+                    if let Some(nearest) = (0..before.len())
+                        .filter(|&j| !before[j].is_empty())
+                        .min_by_key(|&j| (j.abs_diff(i), j))
+                    {
+                        lane.turns = before[nearest].clone();
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn approach(&self, direction: Airt) -> &[Lane] {
+        &self.approaches[direction.index()]
+    }
+
+    pub fn exit_lanes(&self, direction: Airt) -> usize {
+        self.exits[direction.index()]
+    }
+
+    // Widest stretch of road used by traffic heading in `direction`, in lanes.
+    fn carriageway(&self, direction: Airt) -> usize {
+        self.approach(direction)
+            .len()
+            .max(self.exit_lanes(direction))
+    }
+
+    // Offset from the centre line to the top-left corner of a car in `lane` heading in `direction`, across the direction of travel.
+    pub fn lane_offset(direction: Airt, lane: usize, lane_width: i32) -> i32 {
+        match direction {
+            Airt::Up | Airt::Right => lane as i32 * lane_width,
+            Airt::Down | Airt::Left => -(lane as i32 + 1) * lane_width,
+        }
+    }
+
+    // The lane a car ends up in after making `turn` from `lane`.
+    pub fn exit_lane(&self, start: Airt, lane: usize, turn: Turn) -> usize {
+        let exits = self.exit_lanes(start.turn(turn)).max(1);
+        match turn {
+            Turn::Left => 0,
+            Turn::Straight => lane.min(exits - 1),
+            Turn::Right => exits - 1,
+        }
+    }
+
+    // The edges of the junction relative to its centre: (top, bottom, left, right).
+    pub fn bounds(&self, lane_width: i32) -> (i32, i32, i32, i32) {
+        match self.kind {
+            Kind::Crossroad => (
+                -(self.carriageway(Airt::Left) as i32) * lane_width,
+                self.carriageway(Airt::Right) as i32 * lane_width,
+                -(self.carriageway(Airt::Down) as i32) * lane_width,
+                self.carriageway(Airt::Up) as i32 * lane_width,
+            ),
+            Kind::Roundabout { .. } => {
+                let radius = self.radius(lane_width);
+                (-radius, radius, -radius, radius)
+            }
+        }
+    }
+
+    // Radius of the outside edge of a roundabout's circulating lanes.
+    pub fn radius(&self, lane_width: i32) -> i32 {
+        match self.kind {
+            Kind::Crossroad => 0,
+            Kind::Roundabout {
+                island,
+                circulating,
+            } => (island + circulating) as i32 * lane_width,
+        }
+    }
+
+    // How far from the centre the arm that traffic heading in `inbound` drives in on meets the junction.
+    pub fn reach(&self, inbound: Airt, lane_width: i32) -> i32 {
+        let (top, bottom, left, right) = self.bounds(lane_width);
+        match self.kind {
+            Kind::Crossroad => match inbound {
+                Airt::Up => bottom,
+                Airt::Down => -top,
+                Airt::Left => right,
+                Airt::Right => -left,
+            },
+            Kind::Roundabout { .. } => {
+                // The arm runs up to where its outer edge meets the circle.
+                let width = self
+                    .approach(inbound)
+                    .len()
+                    .max(self.exit_lanes(inbound.opposite())) as f64
+                    * lane_width as f64;
+                let radius = self.radius(lane_width) as f64;
+                (radius * radius - width * width).max(0.0).sqrt() as i32
+            }
+        }
+    }
+
+    // Distance along `direction` from the centre to the top-left corner of a car in `lane` that is just outside the junction, on the arm it enters from when heading that way. At a roundabout that is as close as a car can be without a car going round the outside lane of the ring catching its footprint, which is further out for lanes nearer the centre line, so that a car waiting to go on is never in the way of the ring.
+    fn entry_along(&self, direction: Airt, lane: usize, lane_width: i32) -> i32 {
+        let reach = match self.kind {
+            Kind::Crossroad => self.reach(direction, lane_width),
+            Kind::Roundabout { .. } => {
+                let half = lane_width as f64 / 2.0;
+                // The centre of a car going round the outside lane, and the nearest it gets across the arm to the centre of this car.
+                let outside = self.radius(lane_width) as f64 - half;
+                let across = (lane as f64 * lane_width as f64 - half).max(0.0);
+                ((outside * outside - across * across).max(0.0).sqrt() + half).ceil() as i32 + 1
+            }
+        };
+        match direction {
+            Airt::Up | Airt::Left => reach,
+            Airt::Down | Airt::Right => -reach - lane_width,
+        }
+    }
+
+    // The way through the junction centred on `centre` for a car in `lane` heading in `start` and making `turn`. The first point is where the car enters the junction and the last where it has left it.
+    pub fn points(
+        &self,
+        centre: (i32, i32),
+        start: Airt,
+        lane: usize,
+        turn: Turn,
+        lane_width: i32,
+    ) -> Vec<(f64, f64)> {
+        let end = start.turn(turn);
+        let exit_lane = self.exit_lane(start, lane, turn);
+        // The junction centre along and across a direction of travel.
+        let along = |direction: Airt| {
+            if direction.is_vertical() {
+                centre.1
+            } else {
+                centre.0
+            }
+        };
+        let across = |direction: Airt| {
+            if direction.is_vertical() {
+                centre.0
+            } else {
+                centre.1
+            }
+        };
+        let start_across = across(start) + Geometry::lane_offset(start, lane, lane_width);
+        let end_across = across(end) + Geometry::lane_offset(end, exit_lane, lane_width);
+        let entry = point(
+            start,
+            along(start) + self.entry_along(start, lane, lane_width),
+            start_across,
+        );
+        let exit = point(
+            end,
+            along(end) + self.entry_along(end.opposite(), exit_lane, lane_width),
+            end_across,
+        );
+
+        let mut points = vec![entry];
+        match self.kind {
+            Kind::Crossroad if end != start => {
+                // Drive up to the exit lane, then turn into it.
+                points.push(point(start, end_across, start_across));
+            }
+            Kind::Crossroad => {
+                // Going straight on, a car drifts across into its exit lane while crossing the junction.
+            }
+            Kind::Roundabout {
+                island,
+                circulating,
+            } => {
+                // Work with the centre of the car, which is what follows the circle.
+                let half = lane_width as f64 / 2.0;
+                let ring = match turn {
+                    Turn::Right => circulating - 1,
+                    _ => lane.min(circulating - 1),
+                };
+                let radius = (island + ring) as f64 * lane_width as f64 + half;
+                // Where a lane `offset` from the centre line, on the arm on the `side` of the centre, crosses the circle.
+                let angle = |direction: Airt, side: i32, offset: f64| {
+                    let along = side as f64 * (radius * radius - offset * offset).max(0.0).sqrt();
+                    let (x, y) = if direction.is_vertical() {
+                        (offset, along)
+                    } else {
+                        (along, offset)
+                    };
+                    y.atan2(x)
+                };
+                let from = angle(
+                    start,
+                    -start.sign(),
+                    Geometry::lane_offset(start, lane, lane_width) as f64 + half,
+                );
+                let to = angle(
+                    end,
+                    end.sign(),
+                    Geometry::lane_offset(end, exit_lane, lane_width) as f64 + half,
+                );
+                // Traffic drives round anticlockwise, which on screen is towards smaller angles.
+                let mut sweep = (from - to).rem_euclid(std::f64::consts::TAU);
+                if sweep < 1e-6 {
+                    sweep = std::f64::consts::TAU;
+                }
+                let steps = (sweep.to_degrees() / 10.0).ceil().max(1.0) as usize;
+                for step in 0..=steps {
+                    let theta = from - sweep * step as f64 / steps as f64;
+                    points.push((
+                        centre.0 as f64 + radius * theta.cos() - half,
+                        centre.1 as f64 + radius * theta.sin() - half,
+                    ));
+                }
+            }
+        }
+        points.push(exit);
+
+        points
+    }
+}
+
+// Converts a distance along `direction` and an offset across it into screen coordinates.
+pub fn point(direction: Airt, along: i32, across: i32) -> (f64, f64) {
+    if direction.is_vertical() {
+        (across as f64, along as f64)
+    } else {
+        (along as f64, across as f64)
+    }
+}
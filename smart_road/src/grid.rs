@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+// The positions of numbered things, such as cars by `index`, with a uniform grid laid over them, so that the ones in an area can be found without looking at them all.
+pub struct Grid {
+    size: i32, // The width and height of a cell
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    positions: Vec<(i32, i32)>,
+}
+
+impl Grid {
+    // Numbers the positions in order.
+    pub fn new(size: i32, positions: Vec<(i32, i32)>) -> Self {
+        let mut cells: HashMap<(i32, i32), Vec<usize>> = HashMap::new();
+        for (index, &(x, y)) in positions.iter().enumerate() {
+            cells
+                .entry((x.div_euclid(size), y.div_euclid(size)))
+                .or_default()
+                .push(index);
+        }
+        Grid {
+            size,
+            cells,
+            positions,
+        }
+    }
+
+    pub fn position(&self, index: usize) -> (i32, i32) {
+        self.positions[index]
+    }
+
+    pub fn set(&mut self, index: usize, x: i32, y: i32) {
+        let (old_x, old_y) = self.positions[index];
+        let from = (old_x.div_euclid(self.size), old_y.div_euclid(self.size));
+        let to = (x.div_euclid(self.size), y.div_euclid(self.size));
+        if from != to {
+            if let Some(cell) = self.cells.get_mut(&from) {
+                cell.retain(|&other| other != index);
+            }
+            self.cells.entry(to).or_default().push(index);
+        }
+        self.positions[index] = (x, y);
+    }
+
+    // The things positioned in the cells that the area from (`left`, `top`) to (`right`, `bottom`) touches, with where they are. Some may be outside the area, so callers still check.
+    pub fn near(
+        &self,
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    ) -> impl Iterator<Item = (usize, i32, i32)> + '_ {
+        let columns = left.div_euclid(self.size)..=right.div_euclid(self.size);
+        let rows = top.div_euclid(self.size)..=bottom.div_euclid(self.size);
+        columns
+            .flat_map(move |column| rows.clone().map(move |row| (column, row)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .map(|&index| {
+                let (x, y) = self.positions[index];
+                (index, x, y)
+            })
+    }
+}
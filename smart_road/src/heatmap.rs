@@ -0,0 +1,60 @@
+use std::collections::HashMap;
+
+use crate::{cars::Traffic, events::Event, types::Dimensions};
+
+// How long cars were held up, and how many close calls there were, in each cell of a grid over the whole network, to show which conflict points the junctions handle worst. The cells are a lane wide, like those junctions reserve. Recorded by giving `Traffic` one.
+pub struct Heatmap {
+    pub cell: i32,
+    pub columns: usize,
+    pub rows: usize,
+    pub stopped: Vec<u32>, // Frames cars spent held up in each cell, row by row
+    pub close_calls: Vec<u32>, // Close calls in each cell, placed halfway between the two cars
+}
+
+impl Heatmap {
+    pub fn new(dimensions: &Dimensions) -> Self {
+        let cell = dimensions.lane_width;
+        let columns = (dimensions.network.width / cell + 1) as usize;
+        let rows = (dimensions.network.height / cell + 1) as usize;
+        Heatmap {
+            cell,
+            columns,
+            rows,
+            stopped: vec![0; columns * rows],
+            close_calls: vec![0; columns * rows],
+        }
+    }
+
+    // The cell a point is in, if it is on the network.
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let (column, row) = (x.div_euclid(self.cell), y.div_euclid(self.cell));
+        if column < 0 || row < 0 || column as usize >= self.columns || row as usize >= self.rows {
+            return None;
+        }
+        Some(row as usize * self.columns + column as usize)
+    }
+
+    pub fn record(&mut self, traffic: &Traffic) {
+        for car in traffic.cars.iter().filter(|car| car.halted) {
+            if let Some(index) = self.index(car.x, car.y) {
+                self.stopped[index] += 1;
+            }
+        }
+        let positions = traffic
+            .cars
+            .iter()
+            .map(|car| (car.id, (car.x, car.y)))
+            .collect::<HashMap<u64, (i32, i32)>>();
+        for (_, event) in &traffic.events {
+            let Event::CloseCall { cars: (a, b) } = event else {
+                continue;
+            };
+            // A car that left on the same frame has nowhere to be counted.
+            if let (Some(&(ax, ay)), Some(&(bx, by))) = (positions.get(a), positions.get(b)) {
+                if let Some(index) = self.index((ax + bx) / 2, (ay + by) / 2) {
+                    self.close_calls[index] += 1;
+                }
+            }
+        }
+    }
+}
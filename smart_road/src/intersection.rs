@@ -0,0 +1,225 @@
+use std::collections::HashMap;
+
+use crate::{path::Path, types::Airt, v2i::Message};
+
+// The intersection logic of one junction. It hands out arrival times, keeping track of which lane-sized cells of the junction each car it has advised will be in on each frame, so that cars whose ways through the junction cross don't get there together.
+pub struct Intersection {
+    // The car each cell is kept for, keyed by (column, row, frame), with columns and rows counted from the junction centre.
+    reserved: HashMap<(i32, i32, u64), u64>,
+    // The last arrival handed out at each entry point, with the advised speed, so that a car isn't told to get there before the car in front of it.
+    queued: HashMap<(i32, i32), (u64, i32)>,
+    pub margin: u64, // Frames either side of a car's expected position that its cells are kept free for
+    // Approaches a controller is keeping cars back on, by the way the cars are heading (indexed with `Airt::index`). Cars on them don't ask for a slot or drive onto the junction.
+    pub held: [bool; 4],
+    // Cars driven by people waiting at the line for a gap, with the way each is heading as it comes in and the frame it started waiting on.
+    waiting: HashMap<u64, (Airt, u64)>,
+    // The person the junction is making a gap for, with the way they are heading.
+    serving: Option<(u64, Airt)>,
+}
+
+// A car's way through the junction, as it asks for an arrival time.
+#[derive(Clone)]
+pub struct Request {
+    pub path: Path,
+    // The way the car is heading as it comes in.
+    pub start: Airt,
+    // Where along `path` the car enters and leaves the junction, and where it was when it asked.
+    pub entry: f64,
+    pub exit: f64,
+    pub travelled: f64,
+    // The fastest the car is willing to go.
+    pub speed: i32,
+    // The frame the request was made on.
+    pub frame: u64,
+}
+
+// The speed to drive at so as to reach the entry line on frame `arrival`.
+#[derive(Clone, Copy)]
+pub struct Advice {
+    pub speed: i32,
+    pub arrival: u64,
+}
+
+// Frames either side of a car's expected position that its cells are kept free for on a dry road, to allow for the car not keeping to its advice exactly.
+pub const MARGIN: u64 = 2;
+
+// Frames a person may wait at the line for a gap before the junction makes one for them, about ten seconds.
+pub const PATIENCE: u64 = 600;
+
+impl Default for Intersection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Intersection {
+    pub fn new() -> Self {
+        Intersection {
+            reserved: HashMap::new(),
+            queued: HashMap::new(),
+            margin: MARGIN,
+            held: [false; 4],
+            waiting: HashMap::new(),
+            serving: None,
+        }
+    }
+
+    // Forgets the cells for frames that have gone by.
+    pub fn tick(&mut self, frame: u64) {
+        let margin = self.margin;
+        self.reserved.retain(|&(_, _, at), _| at + margin >= frame);
+        self.queued.retain(|_, &mut (at, _)| at + margin >= frame);
+    }
+
+    // Answers a message from a car. Requests get a grant or a denial, and a beacon from a car that has left the junction frees whatever is still kept for it.
+    pub fn answer(
+        &mut self,
+        message: Message,
+        centre: (i32, i32),
+        earliest: u64,
+        lane_width: i32,
+    ) -> Option<Message> {
+        match message {
+            Message::Request {
+                car,
+                junction,
+                sequence,
+                request,
+            } => Some(
+                match self.advise(car, &request, centre, earliest, lane_width) {
+                    Some(advice) => Message::Grant {
+                        car,
+                        junction,
+                        sequence,
+                        advice,
+                    },
+                    None => Message::Deny {
+                        car,
+                        junction,
+                        sequence,
+                    },
+                },
+            ),
+            Message::Beacon { car, past_exit, .. } => {
+                if past_exit >= 0.0 {
+                    self.release(car);
+                }
+                None
+            }
+            Message::Grant { .. } | Message::Deny { .. } => None,
+        }
+    }
+
+    // The fastest speed up to `request.speed` at which the car's way through the junction is free, arriving no sooner than frame `earliest`, and reserves it. Whatever was kept for the car before is given up, as the car only asks again when it has no advice it can use. There is no advice if the junction is too busy at every speed, or while a gap is being made for a person coming in from another side.
+    pub fn advise(
+        &mut self,
+        car: u64,
+        request: &Request,
+        centre: (i32, i32),
+        earliest: u64,
+        lane_width: i32,
+    ) -> Option<Advice> {
+        self.release(car);
+        if self.serving.is_some_and(|(_, side)| side != request.start) {
+            return None;
+        }
+        let (x, y) = request.path.position(request.entry);
+        let entry = (x.round() as i32, y.round() as i32);
+        // Leave the car in front time to get a car's length past the entry.
+        let behind = self.queued.get(&entry).map_or(0, |&(at, speed)| {
+            at + (2.0 * lane_width as f64 / speed as f64).ceil() as u64
+        });
+        let distance = (request.entry - request.travelled).max(0.0);
+        let advice = (1..=request.speed.max(1)).rev().find_map(|speed| {
+            let arrival = (request.frame + (distance / speed as f64).ceil() as u64)
+                .max(earliest)
+                .max(behind);
+            let cells = cells(request, speed, arrival, self.margin, centre, lane_width);
+            if cells.iter().any(|cell| self.reserved.contains_key(cell)) {
+                return None;
+            }
+            self.reserved
+                .extend(cells.into_iter().map(|cell| (cell, car)));
+            Some(Advice { speed, arrival })
+        })?;
+        self.queued.insert(entry, (advice.arrival, advice.speed));
+        Some(advice)
+    }
+
+    // Keeps the cells a car that can't ask will cover, going on at the speed it is going now from frame `request.frame`, so that no slot is given over them. A car still on its way is expected at the entry once it has covered the distance to it. Cells already kept for a car with a slot stay kept for it: taking them over would free them for somebody else when this car's cells are next given up, while the car with the slot is still coming.
+    pub fn sense(&mut self, car: u64, request: &Request, centre: (i32, i32), lane_width: i32) {
+        self.release(car);
+        let speed = request.speed.max(1);
+        let distance = (request.entry - request.travelled).max(0.0);
+        let arrival = request.frame + (distance / speed as f64) as u64;
+        let cells = cells(request, speed, arrival, self.margin, centre, lane_width);
+        for cell in cells {
+            self.reserved.entry(cell).or_insert(car);
+        }
+    }
+
+    // Notes the cars driven by people that are waiting at the line on `frame`, with the way each is heading, and whether any car driven by a person is `crossing` the junction. A driver gives way to every car coming from another side, so while those keep being given slots, one may never see a gap. Once somebody has waited `PATIENCE` frames, the one who has waited longest is given a gap: cars from other sides aren't given slots until they and whoever went with them are across, as the slots are planned round where people are expected to be and somebody held up on the junction can leave cars stuck behind each other.
+    pub fn wait(&mut self, cars: Vec<(u64, Airt)>, crossing: bool, frame: u64) {
+        let before = std::mem::take(&mut self.waiting);
+        self.waiting = cars
+            .into_iter()
+            .map(|(car, side)| {
+                let since = before.get(&car).map_or(frame, |&(_, since)| since);
+                (car, (side, since))
+            })
+            .collect();
+        if self
+            .serving
+            .is_some_and(|(car, _)| crossing || self.waiting.contains_key(&car))
+        {
+            return;
+        }
+        self.serving = self
+            .waiting
+            .iter()
+            .filter(|&(_, &(_, since))| frame >= since + PATIENCE)
+            .min_by_key(|&(&car, &(_, since))| (since, car))
+            .map(|(&car, &(side, _))| (car, side));
+    }
+
+    // The cells kept on `frame`, as (column, row) from the junction centre, with the car each is kept for.
+    pub fn kept(&self, frame: u64) -> impl Iterator<Item = ((i32, i32), u64)> + '_ {
+        self.reserved
+            .iter()
+            .filter(move |&(&(_, _, at), _)| at == frame)
+            .map(|(&(column, row, _), &car)| ((column, row), car))
+    }
+
+    fn release(&mut self, car: u64) {
+        self.reserved.retain(|_, &mut holder| holder != car);
+    }
+}
+
+// The cells the car covers on each frame while crossing at `speed` from `arrival`, widened by `margin` frames.
+fn cells(
+    request: &Request,
+    speed: i32,
+    arrival: u64,
+    margin: u64,
+    centre: (i32, i32),
+    lane_width: i32,
+) -> Vec<(i32, i32, u64)> {
+    let steps = ((request.exit - request.entry) / speed as f64).ceil() as u64;
+    let mut cells = Vec::new();
+    for step in 0..=steps {
+        let distance = (request.entry + (step * speed as u64) as f64).min(request.exit);
+        let (x, y) = request.path.position(distance);
+        let (left, top) = (x.round() as i32 - centre.0, y.round() as i32 - centre.1);
+        let columns = left.div_euclid(lane_width)..=(left + lane_width - 1).div_euclid(lane_width);
+        let rows = top.div_euclid(lane_width)..=(top + lane_width - 1).div_euclid(lane_width);
+        for column in columns {
+            for row in rows.clone() {
+                let at = arrival + step;
+                for frame in at.saturating_sub(margin)..=at + margin {
+                    cells.push((column, row, frame));
+                }
+            }
+        }
+    }
+    cells
+}
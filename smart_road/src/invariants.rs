@@ -0,0 +1,182 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use crate::{
+    cars::{Car, Traffic},
+    grid::Grid,
+    network::Network,
+    types::{Airt, Dimensions},
+};
+
+// Frames a car may go without moving before it is reported stuck, about a minute.
+pub const STUCK: u64 = 3750;
+
+// Something that should never happen, with the state of every car when it did.
+pub struct Violation {
+    pub frame: u64,
+    pub what: String,
+    pub dump: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Frame {}: {}\n{}", self.frame, self.what, self.dump)
+    }
+}
+
+// Checks the simulation after every frame, for debugging and tests: cars stay in the order they were spawned in, each keeps to the junction it is crossing or to the lanes of the road it is on, goes no faster than the fast speed and doesn't overlap another car, and no car stays put long enough to be stuck. Turned on by giving `Traffic` one.
+pub struct Invariants {
+    pub strict: bool, // Whether to panic at the first violation rather than keep them in `violations`
+    pub stuck: u64,   // Frames without moving before a car is reported stuck
+    pub violations: Vec<Violation>,
+    // Where each car was last seen, by id, since which frame, and whether it has been reported stuck.
+    still: HashMap<u64, ((i32, i32), u64, bool)>,
+}
+
+impl Invariants {
+    pub fn new(strict: bool) -> Self {
+        Invariants {
+            strict,
+            stuck: STUCK,
+            violations: Vec::new(),
+            still: HashMap::new(),
+        }
+    }
+
+    pub fn check(&mut self, traffic: &Traffic, dimensions: &Dimensions) {
+        let mut found = Vec::new();
+        let lane_width = dimensions.lane_width;
+
+        let mut ids = HashSet::new();
+        for (i, car) in traffic.cars.iter().enumerate() {
+            // Cars join the end of the list and leave it without the others moving round, so ids only go up along it.
+            if i > 0 && traffic.cars[i - 1].id >= car.id {
+                found.push(format!(
+                    "car {} comes after car {}, out of spawn order",
+                    car.id,
+                    traffic.cars[i - 1].id
+                ));
+            }
+            ids.insert(car.id);
+            if car.speed < 0 || car.speed > dimensions.speed.fast {
+                found.push(format!(
+                    "car {} is going at {}, outside 0 to {}",
+                    car.id, car.speed, dimensions.speed.fast
+                ));
+            }
+            if let Some(problem) = off_road(car, &dimensions.network, lane_width) {
+                found.push(problem);
+            }
+        }
+
+        let grid = Grid::new(
+            2 * lane_width,
+            traffic.cars.iter().map(|car| (car.x, car.y)).collect(),
+        );
+        for (i, car) in traffic.cars.iter().enumerate() {
+            let near = grid.near(
+                car.x - lane_width,
+                car.y - lane_width,
+                car.x + lane_width,
+                car.y + lane_width,
+            );
+            for (j, x, y) in near {
+                if j > i && (car.x - x).abs() < lane_width && (car.y - y).abs() < lane_width {
+                    found.push(format!(
+                        "cars {} and {} overlap at ({}, {}) and ({}, {})",
+                        car.id, traffic.cars[j].id, car.x, car.y, x, y
+                    ));
+                }
+            }
+        }
+
+        self.still.retain(|id, _| ids.contains(id));
+        for car in &traffic.cars {
+            let (at, since, reported) =
+                self.still
+                    .entry(car.id)
+                    .or_insert(((car.x, car.y), traffic.frame, false));
+            if *at != (car.x, car.y) {
+                *at = (car.x, car.y);
+                *since = traffic.frame;
+                *reported = false;
+            } else if !*reported && traffic.frame - *since >= self.stuck {
+                *reported = true;
+                found.push(format!(
+                    "car {} is stuck, not having moved since frame {}",
+                    car.id, since
+                ));
+            }
+        }
+
+        if found.is_empty() {
+            return;
+        }
+        let violation = Violation {
+            frame: traffic.frame,
+            what: found.join("; "),
+            dump: traffic.dump(),
+        };
+        if self.strict {
+            panic!("{}", violation);
+        }
+        self.violations.push(violation);
+    }
+}
+
+// Where a car is that the layout has no room for, if it is anywhere such. The paths cars follow are worked out from the layout, so this is checked against the layout itself: a car crossing a junction keeps within about a lane of its edges, and one on a road is no further across than the lanes for its way of travel reach, on its side of the centre line.
+fn off_road(car: &Car, network: &Network, lane_width: i32) -> Option<String> {
+    if let Some(crossing) = car.crossing() {
+        let junction = &network.junctions[crossing.junction];
+        let (top, bottom, left, right) = junction.geometry.bounds(lane_width);
+        let (x, y) = (car.x - junction.centre.0, car.y - junction.centre.1);
+        // Cars wait to go onto a roundabout a pixel further out than a lane, to be clear of the ring.
+        let (near, far) = (-lane_width - 1, 1);
+        let inside =
+            (left + near..=right + far).contains(&x) && (top + near..=bottom + far).contains(&y);
+        return (!inside).then(|| {
+            format!(
+                "car {} is at ({}, {}), off junction {} it is crossing",
+                car.id, car.x, car.y, crossing.junction
+            )
+        });
+    }
+    // The way of travel nearest the way the car points, as it points a little to one side while changing lanes.
+    let direction = Airt::ALL
+        .into_iter()
+        .min_by(|a, b| {
+            let off = |direction: Airt| {
+                180.0 - ((car.heading() - direction.angle()).rem_euclid(360.0) - 180.0).abs()
+            };
+            off(*a).total_cmp(&off(*b))
+        })
+        .unwrap();
+    let across = if direction.is_vertical() {
+        car.x
+    } else {
+        car.y
+    };
+    // Roads run straight through the junctions in a column or row, so the car is in the lanes of one of them.
+    let on_road = network
+        .junctions
+        .iter()
+        .enumerate()
+        .any(|(junction, layout)| {
+            let lanes = layout
+                .geometry
+                .approach(direction)
+                .len()
+                .max(layout.geometry.exit_lanes(direction));
+            let nearest = network.across(junction, direction, 0, lane_width);
+            let furthest = network.across(junction, direction, lanes.max(1) - 1, lane_width);
+            lanes > 0 && (nearest.min(furthest)..=nearest.max(furthest)).contains(&across)
+        });
+    (!on_road).then(|| {
+        format!(
+            "car {} heading {:?} is at ({}, {}), outside the lanes that way",
+            car.id, direction, car.x, car.y
+        )
+    })
+}
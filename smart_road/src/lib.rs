@@ -0,0 +1,21 @@
+// The model of the junctions and the traffic through them, without any way of showing it, so that frontends, tools and tests can use it on its own.
+
+pub mod cars;
+pub mod drivers;
+pub mod env;
+pub mod events;
+pub mod experiment;
+pub mod geometry;
+pub mod grid;
+pub mod heatmap;
+pub mod intersection;
+pub mod invariants;
+pub mod metrics;
+pub mod network;
+pub mod path;
+pub mod random;
+pub mod statistics;
+pub mod types;
+pub mod v2i;
+pub mod weather;
+pub mod world;
@@ -0,0 +1,99 @@
+use crate::{cars::Traffic, types::Airt};
+
+// Seconds per frame, as the window runs at.
+pub const FRAME: f64 = 0.016;
+
+// Frames between samples by default, about a second.
+pub const EVERY: u64 = 60;
+
+// How the roads looked on one frame.
+pub struct Sample {
+    pub frame: u64,
+    pub active: usize,    // Cars on the road
+    pub passed: i32,      // Cars through so far
+    pub close_calls: i32, // Close calls so far
+    pub mean_speed: f64,  // Over the cars on the road, in pixels a frame
+    // For each junction, the cars held up on their way to it, by the side they come in from in the order of `Airt::index`.
+    pub queues: Vec<[usize; 4]>,
+    // For each junction, the cars on it.
+    pub occupancy: Vec<usize>,
+}
+
+// Samples taken every `every` frames, so as to see how congestion builds and clears over a run. Recorded by giving `Traffic` one.
+pub struct Series {
+    pub every: u64,
+    pub samples: Vec<Sample>,
+}
+
+impl Series {
+    pub fn new(every: u64) -> Self {
+        Series {
+            every: every.max(1),
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, traffic: &Traffic) {
+        if !traffic.frame.is_multiple_of(self.every) {
+            return;
+        }
+        let junctions = traffic.intersections.len();
+        let mut queues = vec![[0; 4]; junctions];
+        for ((junction, start, _, _), _, halted) in traffic.approaching() {
+            if halted {
+                queues[junction][start.index()] += 1;
+            }
+        }
+        let mut occupancy = vec![0; junctions];
+        for car in &traffic.cars {
+            if let Some(crossing) = car.crossing() {
+                occupancy[crossing.junction] += 1;
+            }
+        }
+        let active = traffic.cars.len();
+        let mean_speed = if active == 0 {
+            0.0
+        } else {
+            traffic.cars.iter().map(|car| car.speed as f64).sum::<f64>() / active as f64
+        };
+        self.samples.push(Sample {
+            frame: traffic.frame,
+            active,
+            passed: traffic.cars_passed,
+            close_calls: traffic.close_calls,
+            mean_speed,
+            queues,
+            occupancy,
+        });
+    }
+
+    // The samples as a table with a row each, with a queue column for each side of each junction and an occupancy column for each junction.
+    pub fn csv(&self) -> String {
+        let junctions = self
+            .samples
+            .last()
+            .map_or(0, |sample| sample.occupancy.len());
+        let mut table = "frame,active,passed,close_calls,mean_speed".to_string();
+        for junction in 0..junctions {
+            for side in Airt::ALL {
+                table += &format!(",queue_{}_{:?}", junction, side).to_lowercase();
+            }
+            table += &format!(",occupancy_{}", junction);
+        }
+        table.push('\n');
+        for sample in &self.samples {
+            table += &format!(
+                "{},{},{},{},{:.3}",
+                sample.frame, sample.active, sample.passed, sample.close_calls, sample.mean_speed
+            );
+            for (queues, occupancy) in sample.queues.iter().zip(&sample.occupancy) {
+                for queue in queues {
+                    table += &format!(",{}", queue);
+                }
+                table += &format!(",{}", occupancy);
+            }
+            table.push('\n');
+        }
+        table
+    }
+}
@@ -0,0 +1,359 @@
+use rand::Rng;
+use std::collections::VecDeque;
+
+use crate::{
+    geometry::{point, Geometry},
+    path::Path,
+    random,
+    types::{Airt, Turn},
+};
+
+// The world the cars drive in: a grid of junctions joined by links. A single crossroad is a 1x1 grid.
+pub struct Network {
+    pub junctions: Vec<Junction>,
+    pub links: Vec<Link>,
+    pub width: i32,
+    pub height: i32,
+}
+
+pub struct Junction {
+    pub centre: (i32, i32),
+    pub geometry: Geometry,
+    // The link leaving this junction in each direction of travel, if there is one. Without a link, traffic leaving that way leaves the world.
+    pub links: [Option<usize>; 4],
+}
+
+// A road between two neighbouring junctions, carrying traffic heading in `direction`.
+pub struct Link {
+    pub from: usize,
+    pub to: usize,
+    pub direction: Airt,
+}
+
+// Where a car's path goes through a junction, as distances along the path.
+#[derive(Clone, Copy)]
+pub struct Crossing {
+    pub junction: usize,
+    pub entry: f64,
+    pub exit: f64,
+}
+
+// One junction on a car's route, and the lane the car uses to go through it.
+#[derive(Clone, Copy)]
+pub struct Leg {
+    pub junction: usize,
+    pub start: Airt,
+    pub lane: usize,
+    pub turn: Turn,
+}
+
+impl Network {
+    // `columns` by `rows` junctions spaced a block apart, all laid out by `geometry`.
+    pub fn grid(
+        columns: usize,
+        rows: usize,
+        block_width: i32,
+        block_height: i32,
+        geometry: impl Fn() -> Geometry,
+    ) -> Self {
+        let mut junctions = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                junctions.push(Junction {
+                    centre: (
+                        block_width / 2 + column as i32 * block_width,
+                        block_height / 2 + row as i32 * block_height,
+                    ),
+                    geometry: geometry(),
+                    links: [None; 4],
+                });
+            }
+        }
+
+        let mut links = Vec::new();
+        for (from, junction) in junctions.iter_mut().enumerate() {
+            let (column, row) = (from % columns, from / columns);
+            for direction in Airt::ALL {
+                let to = match direction {
+                    Airt::Up if row > 0 => from - columns,
+                    Airt::Down if row + 1 < rows => from + columns,
+                    Airt::Left if column > 0 => from - 1,
+                    Airt::Right if column + 1 < columns => from + 1,
+                    _ => continue,
+                };
+                junction.links[direction.index()] = Some(links.len());
+                links.push(Link {
+                    from,
+                    to,
+                    direction,
+                });
+            }
+        }
+
+        Network {
+            junctions,
+            links,
+            width: columns as i32 * block_width,
+            height: rows as i32 * block_height,
+        }
+    }
+
+    // The junction reached by leaving `junction` heading in `direction`.
+    pub fn next(&self, junction: usize, direction: Airt) -> Option<usize> {
+        self.junctions[junction].links[direction.index()].map(|link| self.links[link].to)
+    }
+
+    // Distance between the centres of the junctions at either end of `link`.
+    pub fn link_length(&self, link: usize) -> i32 {
+        let link = &self.links[link];
+        let from = self.junctions[link.from].centre;
+        let to = self.junctions[link.to].centre;
+        (along(link.direction, to) - along(link.direction, from)).abs()
+    }
+
+    // Junctions on the edge of the world where traffic heading in `direction` can drive in.
+    pub fn entries(&self, direction: Airt) -> Vec<usize> {
+        (0..self.junctions.len())
+            .filter(|&junction| {
+                self.next(junction, direction.opposite()).is_none()
+                    && !self.junctions[junction]
+                        .geometry
+                        .approach(direction)
+                        .is_empty()
+            })
+            .collect()
+    }
+
+    // Picks a random way through the network for a car entering at `junction` heading in `start`: a random destination on the edge of the world, reached by the route with the fewest junctions.
+    pub fn route(&self, junction: usize, start: Airt) -> Option<Vec<Leg>> {
+        // Breadth-first search over (junction, heading) states, remembering how each state was reached.
+        let state = |junction: usize, heading: Airt| junction * 4 + heading.index();
+        let mut previous: Vec<Option<(usize, Turn)>> = vec![None; self.junctions.len() * 4];
+        let mut visited = vec![false; self.junctions.len() * 4];
+        let mut destinations = Vec::new();
+        let mut queue = VecDeque::from([(junction, start)]);
+        visited[state(junction, start)] = true;
+
+        while let Some((at, heading)) = queue.pop_front() {
+            let geometry = &self.junctions[at].geometry;
+            for turn in [Turn::Left, Turn::Straight, Turn::Right] {
+                let allowed = geometry
+                    .approach(heading)
+                    .iter()
+                    .any(|lane| lane.turns.contains(&turn));
+                let end = heading.turn(turn);
+                if !allowed || geometry.exit_lanes(end) == 0 {
+                    continue;
+                }
+                match self.next(at, end) {
+                    None => destinations.push((at, heading, turn)),
+                    Some(next) => {
+                        let s = state(next, end);
+                        if !visited[s] && !self.junctions[next].geometry.approach(end).is_empty() {
+                            visited[s] = true;
+                            previous[s] = Some((at, turn));
+                            queue.push_back((next, end));
+                        }
+                    }
+                }
+            }
+        }
+
+        if destinations.is_empty() {
+            return None;
+        }
+        let (mut at, mut heading, last_turn) =
+            destinations[random::rng().gen_range(0..destinations.len())];
+
+        let mut turns = vec![(at, heading, last_turn)];
+        while let Some((from, turn)) = previous[state(at, heading)] {
+            let before = Airt::ALL
+                .into_iter()
+                .find(|&d| d.turn(turn) == heading)
+                .unwrap();
+            turns.push((from, before, turn));
+            at = from;
+            heading = before;
+        }
+        turns.reverse();
+
+        // Cars can start in any lane of the approach, and change lanes before each junction if their lane doesn't allow the turn. After a junction they carry on in the lane the turn leads into.
+        let mut legs: Vec<Leg> = turns
+            .into_iter()
+            .map(|(junction, start, turn)| Leg {
+                junction,
+                start,
+                lane: 0,
+                turn,
+            })
+            .collect();
+        let lanes = self.junctions[legs[0].junction]
+            .geometry
+            .approach(legs[0].start)
+            .len();
+        legs[0].lane = random::rng().gen_range(0..lanes);
+        self.follow(&mut legs);
+
+        Some(legs)
+    }
+
+    // A route for a car in `lane` heading in `start` towards `junction` that begins with a turn the lane allows, for when the driver can't get into the lane they wanted.
+    pub fn reroute(&self, junction: usize, start: Airt, lane: usize) -> Option<Vec<Leg>> {
+        let geometry = &self.junctions[junction].geometry;
+        let turns: Vec<Turn> = geometry.approach(start)[lane]
+            .turns
+            .iter()
+            .copied()
+            .filter(|&turn| geometry.exit_lanes(start.turn(turn)) > 0)
+            .collect();
+        if turns.is_empty() {
+            return None;
+        }
+        let turn = turns[random::rng().gen_range(0..turns.len())];
+        let end = start.turn(turn);
+
+        let mut legs = vec![Leg {
+            junction,
+            start,
+            lane,
+            turn,
+        }];
+        if let Some(next) = self.next(junction, end) {
+            legs.extend(self.route(next, end)?);
+        }
+        self.follow(&mut legs);
+        Some(legs)
+    }
+
+    // Sets the lane of every leg after the first to the lane the turn before it leads into, or the nearest one there is.
+    pub fn follow(&self, legs: &mut [Leg]) {
+        for i in 1..legs.len() {
+            let leg = legs[i - 1];
+            let arriving = self.junctions[leg.junction]
+                .geometry
+                .exit_lane(leg.start, leg.lane, leg.turn);
+            let lanes = self.junctions[legs[i].junction]
+                .geometry
+                .approach(legs[i].start)
+                .len();
+            legs[i].lane = arriving.min(lanes.max(1) - 1);
+        }
+    }
+
+    // The lane nearest `lane` on the approach to `leg`'s junction that the turn can be made from.
+    pub fn turning_lane(&self, leg: &Leg, lane: usize) -> usize {
+        let lanes = self.junctions[leg.junction].geometry.approach(leg.start);
+        (0..lanes.len())
+            .filter(|&i| lanes[i].turns.contains(&leg.turn))
+            .min_by_key(|&i| i.abs_diff(lane))
+            .unwrap_or(lane)
+    }
+
+    // The path along `legs`, from the edge of the world where the car enters to just past the edge where it leaves, and where it crosses each junction.
+    pub fn path(&self, legs: &[Leg], lane_width: i32) -> (Path, Vec<Crossing>) {
+        let first = legs.first().expect("A route needs at least one junction");
+        let start = point(
+            first.start,
+            match first.start {
+                Airt::Up => self.height - lane_width,
+                Airt::Left => self.width - lane_width,
+                Airt::Down | Airt::Right => 0,
+            },
+            self.across(first.junction, first.start, first.lane, lane_width),
+        );
+        self.path_from(vec![start], legs, lane_width)
+    }
+
+    // The same as `path`, but starting from `points` rather than the edge of the world. Used to carry on from where a car is when it changes lanes.
+    pub fn path_from(
+        &self,
+        mut points: Vec<(f64, f64)>,
+        legs: &[Leg],
+        lane_width: i32,
+    ) -> (Path, Vec<Crossing>) {
+        let last = legs.last().expect("A route needs at least one junction");
+
+        // The indices of the first and last points of each junction.
+        let mut spans = Vec::with_capacity(legs.len());
+        for (i, leg) in legs.iter().enumerate() {
+            let junction = &self.junctions[leg.junction];
+            let first = points.len();
+            points.extend(junction.geometry.points(
+                junction.centre,
+                leg.start,
+                leg.lane,
+                leg.turn,
+                lane_width,
+            ));
+            spans.push((leg.junction, first, points.len() - 1));
+
+            if let Some(next) = legs.get(i + 1) {
+                // Change lanes half way along the link if the next approach has fewer lanes than the road the turn leads into.
+                let heading = next.start;
+                let exit_lane = junction.geometry.exit_lane(leg.start, leg.lane, leg.turn);
+                if exit_lane != next.lane {
+                    let forward = heading.sign();
+                    let middle = (along(heading, junction.centre)
+                        + along(heading, self.junctions[next.junction].centre))
+                        / 2;
+                    points.push(point(
+                        heading,
+                        middle - forward * 2 * lane_width,
+                        self.across(leg.junction, heading, exit_lane, lane_width),
+                    ));
+                    points.push(point(
+                        heading,
+                        middle + forward * 2 * lane_width,
+                        self.across(next.junction, heading, next.lane, lane_width),
+                    ));
+                }
+            }
+        }
+
+        let end = last.start.turn(last.turn);
+        let exit_lane = self.junctions[last.junction]
+            .geometry
+            .exit_lane(last.start, last.lane, last.turn);
+        points.push(point(
+            end,
+            match end {
+                Airt::Up | Airt::Left => -1,
+                Airt::Down => self.height - lane_width + 1,
+                Airt::Right => self.width - lane_width + 1,
+            },
+            self.across(last.junction, end, exit_lane, lane_width),
+        ));
+
+        let path = Path::new(points);
+        let crossings = spans
+            .into_iter()
+            .map(|(junction, first, last)| Crossing {
+                junction,
+                entry: path.distance(first),
+                exit: path.distance(last),
+            })
+            .collect();
+
+        (path, crossings)
+    }
+
+    // Position across the direction of travel of a car in `lane` heading in `direction` near `junction`.
+    pub fn across(&self, junction: usize, direction: Airt, lane: usize, lane_width: i32) -> i32 {
+        let centre = self.junctions[junction].centre;
+        let centre = if direction.is_vertical() {
+            centre.0
+        } else {
+            centre.1
+        };
+        centre + Geometry::lane_offset(direction, lane, lane_width)
+    }
+}
+
+// The coordinate of `position` along `direction`.
+fn along(direction: Airt, position: (i32, i32)) -> i32 {
+    if direction.is_vertical() {
+        position.1
+    } else {
+        position.0
+    }
+}
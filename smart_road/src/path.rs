@@ -0,0 +1,79 @@
+// A car's route through the world as a polyline. The points are the positions of the top-left corner of the car, like `Car::x` and `Car::y`, so a car at distance `d` along the path occupies the lane-sized square starting at `position(d)`.
+#[derive(Clone)]
+pub struct Path {
+    points: Vec<(f64, f64)>,
+    // Distance along the path at which each point is reached.
+    distances: Vec<f64>,
+}
+
+impl Path {
+    pub fn new(points: Vec<(f64, f64)>) -> Self {
+        assert!(!points.is_empty(), "A path needs at least one point");
+
+        let mut distances = Vec::with_capacity(points.len());
+        let mut total = 0.0;
+        distances.push(total);
+        for pair in points.windows(2) {
+            let (dx, dy) = (pair[1].0 - pair[0].0, pair[1].1 - pair[0].1);
+            total += (dx * dx + dy * dy).sqrt();
+            distances.push(total);
+        }
+
+        Path { points, distances }
+    }
+
+    pub fn length(&self) -> f64 {
+        *self.distances.last().unwrap()
+    }
+
+    // Distance along the path at which `points[index]` is reached.
+    pub fn distance(&self, index: usize) -> f64 {
+        self.distances[index]
+    }
+
+    // The index of the segment that `distance` falls on, counting the segment from `points[i]` to `points[i + 1]` as `i`.
+    fn segment(&self, distance: f64) -> usize {
+        let last = self.points.len().saturating_sub(2);
+        (0..=last)
+            .find(|&i| distance < self.distances[i + 1])
+            .unwrap_or(last)
+    }
+
+    pub fn position(&self, distance: f64) -> (f64, f64) {
+        if self.points.len() == 1 || distance <= 0.0 {
+            return self.points[0];
+        }
+        if distance >= self.length() {
+            return *self.points.last().unwrap();
+        }
+
+        let i = self.segment(distance);
+        let (start, end) = (self.points[i], self.points[i + 1]);
+        let span = self.distances[i + 1] - self.distances[i];
+        let t = if span > 0.0 {
+            (distance - self.distances[i]) / span
+        } else {
+            0.0
+        };
+
+        (
+            start.0 + (end.0 - start.0) * t,
+            start.1 + (end.1 - start.1) * t,
+        )
+    }
+
+    // Heading in degrees at `distance`, measured like the car texture rotation: 0 is up and 90 is right.
+    pub fn heading(&self, distance: f64) -> f64 {
+        if self.points.len() == 1 {
+            return 0.0;
+        }
+
+        let mut i = self.segment(distance.max(0.0));
+        // Zero-length segments have no direction of their own, so look further along.
+        while i + 2 < self.points.len() && self.distances[i + 1] == self.distances[i] {
+            i += 1;
+        }
+        let (start, end) = (self.points[i], self.points[i + 1]);
+        (end.0 - start.0).atan2(start.1 - end.1).to_degrees()
+    }
+}
@@ -0,0 +1,149 @@
+// The mean and spread of some numbers, such as one measure over runs with different seeds.
+pub struct Summary {
+    pub count: usize,
+    pub mean: f64,
+    pub variance: f64, // The sample variance, dividing by one less than the count
+}
+
+impl Summary {
+    pub fn of(samples: &[f64]) -> Self {
+        let count = samples.len();
+        let mean = samples.iter().sum::<f64>() / count as f64;
+        let variance = if count < 2 {
+            0.0
+        } else {
+            samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (count - 1) as f64
+        };
+        Summary {
+            count,
+            mean,
+            variance,
+        }
+    }
+
+    // Half the width of the 95% confidence interval for the mean, from Student's t distribution. There is no interval from a single sample.
+    pub fn margin(&self) -> f64 {
+        if self.count < 2 {
+            return f64::NAN;
+        }
+        let freedom = (self.count - 1) as f64;
+        t_quantile(0.975, freedom) * (self.variance / self.count as f64).sqrt()
+    }
+
+    fn squared_error(&self) -> f64 {
+        self.variance / self.count as f64
+    }
+}
+
+// Welch's t-test of whether two means differ, without assuming the spreads are the same. Gives t and the two-sided p-value.
+pub fn welch(a: &Summary, b: &Summary) -> (f64, f64) {
+    let squared_error = a.squared_error() + b.squared_error();
+    let difference = a.mean - b.mean;
+    if squared_error == 0.0 {
+        // Nothing varied, so the means either match exactly or plainly differ.
+        return if difference == 0.0 {
+            (0.0, 1.0)
+        } else {
+            (difference.signum() * f64::INFINITY, 0.0)
+        };
+    }
+    let t = difference / squared_error.sqrt();
+    // The Welch–Satterthwaite degrees of freedom.
+    let freedom = squared_error.powi(2)
+        / (a.squared_error().powi(2) / (a.count - 1) as f64
+            + b.squared_error().powi(2) / (b.count - 1) as f64);
+    (t, 2.0 * (1.0 - t_cdf(t.abs(), freedom)))
+}
+
+// The chance that Student's t with `freedom` degrees of freedom is at most `t`.
+fn t_cdf(t: f64, freedom: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(freedom / 2.0, 0.5, freedom / (freedom + t * t));
+    if t >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+// The `t` below which Student's t falls with chance `p`, found by bisection.
+fn t_quantile(p: f64, freedom: f64) -> f64 {
+    let (mut low, mut high) = (-1000.0, 1000.0);
+    for _ in 0..100 {
+        let middle = (low + high) / 2.0;
+        if t_cdf(middle, freedom) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    (low + high) / 2.0
+}
+
+// The regularised incomplete beta function, by its continued fraction, as in Numerical Recipes.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The fraction converges quickly on this side, and the other side follows by symmetry.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+fn continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut fraction = d;
+    for m in 1..300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            fraction *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    fraction
+}
+
+// The log of the gamma function, by Lanczos' approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.180_091_729_471_46,
+        -86.505_320_329_416_77,
+        24.014_098_240_830_91,
+        -1.231_739_572_450_155,
+        0.001_208_650_973_866_179,
+        -0.000_005_395_239_384_953,
+    ];
+    let mut series = 1.000_000_000_190_015;
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate() {
+        series += coefficient / (x + 1.0 + i as f64);
+    }
+    let t = x + 5.5;
+    (2.0 * std::f64::consts::PI).sqrt().ln() + (series / x).ln() + (x + 0.5) * t.ln() - t
+}
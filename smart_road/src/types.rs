@@ -0,0 +1,94 @@
+use crate::{drivers::Profile, network::Network, v2i::ChannelConfig, weather::Weather};
+
+pub struct Dimensions {
+    pub window_width: i32,
+    pub window_height: i32,
+    pub half_width: i32,
+    pub half_height: i32,
+    pub lane_width: i32,
+    pub speed: Speed,
+    pub network: Network,
+    pub channel: ChannelConfig, // The radio between cars and junctions
+    pub penetration: f64,       // The share of cars that are connected, from 0 to 1
+    pub profiles: Vec<Profile>, // The kinds of driver cars are given
+    pub weather: Weather,
+}
+
+pub struct Speed {
+    pub fast: i32,
+    pub default: i32,
+    pub slow: i32,
+}
+
+// These directions are all from our point of view as we look at the screen. They describe a car's initial direction and its direction after it's turned, both from our perspective.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum Airt {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Airt {
+    pub const ALL: [Airt; 4] = [Airt::Up, Airt::Down, Airt::Left, Airt::Right];
+
+    // Position of this direction in `ALL`, for tables indexed by direction.
+    pub fn index(self) -> usize {
+        match self {
+            Airt::Up => 0,
+            Airt::Down => 1,
+            Airt::Left => 2,
+            Airt::Right => 3,
+        }
+    }
+
+    pub fn opposite(self) -> Airt {
+        match self {
+            Airt::Up => Airt::Down,
+            Airt::Down => Airt::Up,
+            Airt::Left => Airt::Right,
+            Airt::Right => Airt::Left,
+        }
+    }
+
+    // Whether driving this way makes the coordinate along the direction grow (1) or shrink (-1).
+    pub fn sign(self) -> i32 {
+        match self {
+            Airt::Up | Airt::Left => -1,
+            Airt::Down | Airt::Right => 1,
+        }
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Airt::Up | Airt::Down)
+    }
+
+    // The angle used to rotate the car textures, which are drawn facing up.
+    pub fn angle(self) -> f64 {
+        match self {
+            Airt::Up => 0.0,
+            Airt::Down => 180.0,
+            Airt::Left => -90.0,
+            Airt::Right => 90.0,
+        }
+    }
+
+    // The direction a driver heading this way ends up in after making `turn`. Traffic drives on the right.
+    pub fn turn(self, turn: Turn) -> Airt {
+        match (self, turn) {
+            (_, Turn::Straight) => self,
+            (Airt::Up, Turn::Left) | (Airt::Down, Turn::Right) => Airt::Left,
+            (Airt::Up, Turn::Right) | (Airt::Down, Turn::Left) => Airt::Right,
+            (Airt::Left, Turn::Right) | (Airt::Right, Turn::Left) => Airt::Up,
+            (Airt::Left, Turn::Left) | (Airt::Right, Turn::Right) => Airt::Down,
+        }
+    }
+}
+
+// A movement through a junction, from the driver's point of view.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Turn {
+    Left,
+    Straight,
+    Right,
+}
@@ -0,0 +1,114 @@
+use rand::Rng;
+
+use crate::{
+    intersection::{Advice, Request},
+    random,
+};
+
+// How the simulated radio between cars and junctions behaves. Delays are in frames.
+#[derive(Clone, Copy, Debug)]
+pub struct ChannelConfig {
+    pub delay: u64,
+    // Up to this many extra frames, picked at random for each message.
+    pub jitter: u64,
+    // The chance of a message never arriving, from 0 to 1.
+    pub loss: f64,
+}
+
+impl ChannelConfig {
+    // The longest a message can take to arrive.
+    pub fn worst_delay(&self) -> u64 {
+        self.delay + self.jitter
+    }
+}
+
+// Messages between a car, named by its `id`, and the intersection logic of a junction. `sequence` ties a reply to the request it answers, so that a car can tell an answer to an old request from an answer to its latest one.
+pub enum Message {
+    Request {
+        car: u64,
+        junction: usize,
+        sequence: u64,
+        request: Request,
+    },
+    Grant {
+        car: u64,
+        junction: usize,
+        sequence: u64,
+        advice: Advice,
+    },
+    Deny {
+        car: u64,
+        junction: usize,
+        sequence: u64,
+    },
+    // Sent every frame by a car holding a grant for the junction, with how far it has gone past the junction's exit (negative before it gets there).
+    Beacon {
+        car: u64,
+        junction: usize,
+        past_exit: f64,
+    },
+}
+
+impl Message {
+    pub fn car(&self) -> u64 {
+        match *self {
+            Message::Request { car, .. }
+            | Message::Grant { car, .. }
+            | Message::Deny { car, .. }
+            | Message::Beacon { car, .. } => car,
+        }
+    }
+
+    pub fn junction(&self) -> usize {
+        match *self {
+            Message::Request { junction, .. }
+            | Message::Grant { junction, .. }
+            | Message::Deny { junction, .. }
+            | Message::Beacon { junction, .. } => junction,
+        }
+    }
+}
+
+// Messages on their way in one direction, held back until they are due.
+pub struct Channel {
+    pub config: ChannelConfig,
+    in_flight: Vec<(u64, Message)>,
+    pub sent: u64,
+    pub lost: u64,
+}
+
+impl Channel {
+    pub fn new(config: ChannelConfig) -> Self {
+        Channel {
+            config,
+            in_flight: Vec::new(),
+            sent: 0,
+            lost: 0,
+        }
+    }
+
+    pub fn send(&mut self, frame: u64, message: Message) {
+        let mut rng = random::rng();
+        self.sent += 1;
+        if rng.gen_bool(self.config.loss.clamp(0.0, 1.0)) {
+            self.lost += 1;
+            return;
+        }
+        let due = frame + self.config.delay + rng.gen_range(0..=self.config.jitter);
+        self.in_flight.push((due, message));
+    }
+
+    // Takes the messages due by `frame`, in the order they arrive.
+    pub fn receive(&mut self, frame: u64) -> Vec<Message> {
+        self.in_flight.sort_by_key(|&(due, _)| due);
+        let count = self
+            .in_flight
+            .iter()
+            .take_while(|&&(due, _)| due <= frame)
+            .count();
+        self.in_flight
+            .drain(..count)
+            .map(|(_, message)| message)
+            .collect()
+    }
+}
@@ -0,0 +1,69 @@
+use crate::intersection::MARGIN;
+
+// The state of the roads, which every car drives to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Weather {
+    Dry,
+    Wet,
+    Snow,
+    Fog,
+}
+
+impl Weather {
+    // The weather after this one, going round them all.
+    pub fn next(self) -> Self {
+        match self {
+            Weather::Dry => Weather::Wet,
+            Weather::Wet => Weather::Snow,
+            Weather::Snow => Weather::Fog,
+            Weather::Fog => Weather::Dry,
+        }
+    }
+
+    // A multiple of the speeds drivers would like to go at.
+    pub fn speed(self) -> f64 {
+        match self {
+            Weather::Dry => 1.0,
+            Weather::Wet => 0.85,
+            Weather::Snow => 0.6,
+            Weather::Fog => 0.7,
+        }
+    }
+
+    // A multiple of how hard cars can brake. With less grip, cars take longer to stop.
+    pub fn grip(self) -> f64 {
+        match self {
+            Weather::Dry | Weather::Fog => 1.0,
+            Weather::Wet => 0.7,
+            Weather::Snow => 0.4,
+        }
+    }
+
+    // How far drivers can see, in lane widths.
+    pub fn sight(self) -> f64 {
+        match self {
+            Weather::Dry => 30.0,
+            Weather::Wet => 25.0,
+            Weather::Snow => 15.0,
+            Weather::Fog => 6.0,
+        }
+    }
+
+    // Frames either side of a car's expected position that junctions keep free for it, as cars keep to their advice less exactly when the going is poor. A car may only enter a junction within this many frames of its arrival time.
+    pub fn margin(self) -> u64 {
+        match self {
+            Weather::Dry => MARGIN,
+            Weather::Wet | Weather::Fog => MARGIN + 1,
+            Weather::Snow => MARGIN + 2,
+        }
+    }
+
+    // The colour of the road surface.
+    pub fn asphalt(self) -> (u8, u8, u8) {
+        match self {
+            Weather::Dry | Weather::Fog => (64, 64, 64),
+            Weather::Wet => (36, 38, 44),
+            Weather::Snow => (150, 150, 156),
+        }
+    }
+}
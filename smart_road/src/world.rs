@@ -0,0 +1,93 @@
+use crate::{
+    drivers::Profile,
+    geometry::Geometry,
+    network::Network,
+    types::{Airt, Dimensions, Speed},
+    v2i::ChannelConfig,
+    weather::Weather,
+};
+
+// The world and everything set up in it, for a window of the given size.
+pub fn dimensions(window_width: i32, window_height: i32) -> Dimensions {
+    const LANE_WIDTH: i32 = 16;
+    // Lanes in and out of each junction for each direction of travel: up, down, left, right.
+    const LANES: [usize; 4] = [3, 3, 3, 3];
+    // Junctions in the network, as columns and rows. Each junction gets a block the size of the window.
+    const GRID: (usize, usize) = (1, 1);
+    // Lanes around the island if the junctions are roundabouts, or 0 for crossroads.
+    const CIRCULATING_LANES: usize = 0;
+    // The side of each junction without an arm, for T-junctions.
+    const MISSING_ARM: Option<Airt> = None;
+    // How messages between cars and junctions get through: frames of delay, up to how many extra frames at random, and the chance of a message being lost.
+    const CHANNEL: ChannelConfig = ChannelConfig {
+        delay: 0,
+        jitter: 0,
+        loss: 0.0,
+    };
+    // The share of cars that are connected and take part in junction reservations, from 0 to 1. The rest are driven by people, who wait for a gap at each junction.
+    const PENETRATION: f64 = 1.0;
+    // The state of the roads: Weather::Dry, Wet, Snow or Fog.
+    const WEATHER: Weather = Weather::Dry;
+    // The kinds of driver, how often each turns up, and the ranges their drivers' values are picked from.
+    const PROFILES: [Profile; 3] = [
+        Profile {
+            name: "Cautious",
+            share: 1.0,
+            speed: (0.7, 0.9),
+            reaction: (30, 50),
+            gap: (45.0, 60.0),
+            acceleration: (1, 1),
+            braking: (1, 1),
+            rotation: (5.0, 7.0),
+        },
+        Profile {
+            name: "Normal",
+            share: 2.0,
+            speed: (0.9, 1.1),
+            reaction: (20, 35),
+            gap: (35.0, 45.0),
+            acceleration: (1, 1),
+            braking: (1, 2),
+            rotation: (7.0, 9.0),
+        },
+        Profile {
+            name: "Aggressive",
+            share: 1.0,
+            speed: (1.0, 1.3),
+            reaction: (12, 25),
+            gap: (25.0, 35.0),
+            acceleration: (1, 2),
+            braking: (2, 3),
+            rotation: (9.0, 12.0),
+        },
+    ];
+    let speed = Speed {
+        fast: LANE_WIDTH * 3 / 4,
+        default: LANE_WIDTH / 2,
+        slow: LANE_WIDTH / 4,
+    };
+
+    Dimensions {
+        window_width,
+        window_height,
+        half_width: window_width / 2,
+        half_height: window_height / 2,
+        lane_width: LANE_WIDTH,
+        speed,
+        network: Network::grid(GRID.0, GRID.1, window_width, window_height, || {
+            let mut geometry = if CIRCULATING_LANES == 0 {
+                Geometry::new(LANES)
+            } else {
+                Geometry::roundabout(LANES, CIRCULATING_LANES)
+            };
+            if let Some(side) = MISSING_ARM {
+                geometry.remove_arm(side);
+            }
+            geometry
+        }),
+        channel: CHANNEL,
+        penetration: PENETRATION,
+        profiles: PROFILES.to_vec(),
+        weather: WEATHER,
+    }
+}
@@ -0,0 +1,33 @@
+// Scenarios with connected cars and cars driven by people sharing a junction.
+
+use smart_road::{
+    cars::Traffic,
+    experiment::{arrive, Policy, Settings},
+    invariants::Invariants,
+    random,
+    weather::Weather,
+};
+
+// With half the cars connected and two a second arriving, somebody at the line could wait for ever for a gap while the junction kept handing slots to cars from the other sides.
+#[test]
+fn people_at_the_line_get_a_gap() {
+    for seed in [2, 3] {
+        let settings = Settings {
+            rate: 2.0,
+            speed: 1.0,
+            policy: Policy::Mixed(0.5),
+            weather: Weather::Dry,
+            seed,
+            frames: 6000,
+            check: true,
+        };
+        random::seed(seed);
+        let dimensions = settings.dimensions();
+        let mut traffic = Traffic::new();
+        traffic.invariants = Some(Invariants::new(true));
+        for _ in 0..settings.frames {
+            arrive(&mut traffic, settings.rate, &dimensions);
+            traffic.update(&dimensions);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+// Property tests: random schedules of cars are run through `Traffic` without a window, checking every frame with `Invariants`. A failing schedule is shrunk to the shortest one that still fails and saved in `properties.regressions` next to this file, which is tried first on every later run, so check that file in.
+
+use proptest::{prelude::*, test_runner::FileFailurePersistence};
+use smart_road::{
+    cars::Traffic,
+    geometry::Geometry,
+    invariants::Invariants,
+    network::Network,
+    random,
+    types::{Airt, Dimensions},
+    v2i::ChannelConfig,
+    weather::Weather,
+    world,
+};
+
+// Frames left after the last spawn for every car to get out, about a minute and a half.
+const DRAIN: u64 = 6000;
+
+// One car in a schedule: frames after the car before it, the side it drives in from, and the seed its route is picked with, which also picks its entry lane and driver.
+#[derive(Clone, Debug)]
+struct Spawn {
+    after: u64,
+    origin: Airt,
+    route: u64,
+}
+
+fn spawn() -> impl Strategy<Value = Spawn> {
+    // Half a second to four seconds apart, which is moderate load for one junction.
+    (30u64..240, 0usize..4, any::<u64>()).prop_map(|(after, origin, route)| Spawn {
+        after,
+        origin: Airt::ALL[origin],
+        route,
+    })
+}
+
+fn weather() -> impl Strategy<Value = Weather> {
+    prop_oneof![
+        Just(Weather::Dry),
+        Just(Weather::Wet),
+        Just(Weather::Snow),
+        Just(Weather::Fog),
+    ]
+}
+
+// The junctions a schedule is run on, with the lanes in and out for each direction of travel, which needn't be the same.
+#[derive(Clone, Copy, Debug)]
+enum Layout {
+    Crossroad([usize; 4]),
+    // With this many lanes going round.
+    Roundabout([usize; 4], usize),
+    // A crossroad without the arm on one side.
+    TJunction([usize; 4], Airt),
+    // Two by two crossroads joined by links.
+    Grid([usize; 4]),
+}
+
+impl Layout {
+    fn geometry(&self) -> Geometry {
+        match *self {
+            Layout::Crossroad(lanes) | Layout::Grid(lanes) => Geometry::new(lanes),
+            Layout::Roundabout(lanes, circulating) => Geometry::roundabout(lanes, circulating),
+            Layout::TJunction(lanes, side) => {
+                let mut geometry = Geometry::new(lanes);
+                geometry.remove_arm(side);
+                geometry
+            }
+        }
+    }
+
+    fn network(&self) -> Network {
+        let columns = match self {
+            Layout::Grid(_) => 2,
+            _ => 1,
+        };
+        Network::grid(columns, columns, 860, 860, || self.geometry())
+    }
+}
+
+fn layout() -> impl Strategy<Value = Layout> {
+    let lanes = || [1usize..=3, 1usize..=3, 1usize..=3, 1usize..=3];
+    prop_oneof![
+        lanes().prop_map(Layout::Crossroad),
+        (lanes(), 1usize..=2)
+            .prop_map(|(lanes, circulating)| Layout::Roundabout(lanes, circulating)),
+        (lanes(), 0usize..4).prop_map(|(lanes, side)| Layout::TJunction(lanes, Airt::ALL[side])),
+        lanes().prop_map(Layout::Grid),
+    ]
+}
+
+// A radio that is late and loses messages, for the runs where connected cars and people share the junctions.
+fn lossy() -> impl Strategy<Value = ChannelConfig> {
+    (4u64..=12, 0u64..=6, 0.01..0.1).prop_map(|(delay, jitter, loss)| ChannelConfig {
+        delay,
+        jitter,
+        loss,
+    })
+}
+
+// A perfect radio half the time, otherwise up to a fifth of a second late, give or take, losing up to one message in ten.
+fn channel() -> impl Strategy<Value = ChannelConfig> {
+    prop_oneof![
+        Just(ChannelConfig {
+            delay: 0,
+            jitter: 0,
+            loss: 0.0,
+        }),
+        (0u64..=12, 0u64..=6, 0.0..0.1).prop_map(|(delay, jitter, loss)| ChannelConfig {
+            delay,
+            jitter,
+            loss,
+        }),
+    ]
+}
+
+fn dimensions(
+    layout: &Layout,
+    channel: ChannelConfig,
+    penetration: f64,
+    weather: Weather,
+) -> Dimensions {
+    let mut dimensions = world::dimensions(860, 860);
+    dimensions.network = layout.network();
+    dimensions.channel = channel;
+    dimensions.penetration = penetration;
+    dimensions.weather = weather;
+    dimensions
+}
+
+// Runs the schedule then lets the roads empty, with the invariants checked on every frame except for stuck cars, which the end of the drain looks for. Gives the traffic and how many cars got onto the road, as a car isn't let on while a queue reaches back to the edge.
+fn run(schedule: &[Spawn], dimensions: &Dimensions) -> (Traffic, i32) {
+    let mut traffic = Traffic::new();
+    let mut invariants = Invariants::new(true);
+    invariants.stuck = u64::MAX;
+    traffic.invariants = Some(invariants);
+    let mut spawned = 0;
+    random::seed(0);
+    for spawn in schedule {
+        for _ in 0..spawn.after {
+            traffic.update(dimensions);
+        }
+        random::seed(spawn.route);
+        let before = traffic.cars.len();
+        traffic.push(spawn.origin, dimensions);
+        spawned += (traffic.cars.len() - before) as i32;
+    }
+    for _ in 0..DRAIN {
+        if traffic.cars.is_empty() {
+            break;
+        }
+        traffic.update(dimensions);
+    }
+    (traffic, spawned)
+}
+
+// Runs the schedule and checks that every car that got onto the road is through by the end of the drain.
+fn drains(schedule: &[Spawn], dimensions: &Dimensions) -> Result<(), TestCaseError> {
+    let (traffic, spawned) = run(schedule, dimensions);
+    prop_assert!(
+        traffic.cars.is_empty(),
+        "Deadlock: {} cars still on the road {} frames after the last spawn\n{}",
+        traffic.cars.len(),
+        DRAIN,
+        traffic.dump()
+    );
+    prop_assert_eq!(traffic.cars_passed, spawned);
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 64,
+        failure_persistence: Some(Box::new(FileFailurePersistence::WithSource("regressions"))),
+        ..ProptestConfig::default()
+    })]
+
+    // Connected cars, cars driven by people and a mix of both never overlap, never leave their paths or break the speed limit, and all get through once spawning stops, whatever the junctions and however the radio behaves.
+    #[test]
+    fn every_car_gets_through(
+        schedule in prop::collection::vec(spawn(), 1..40),
+        layout in layout(),
+        channel in channel(),
+        penetration in prop_oneof![Just(0.0), Just(0.5), Just(1.0)],
+        weather in weather(),
+    ) {
+        drains(&schedule, &dimensions(&layout, channel, penetration, weather))?;
+    }
+
+    // The hardest mix for the junctions to keep apart: connected cars whose messages come late or go missing, and people who can't see far or stop quickly, as when cars held up with a slot were missed by the people looking for a gap.
+    #[test]
+    fn mixed_traffic_gets_through_a_lossy_radio_on_a_slippery_road(
+        schedule in prop::collection::vec(spawn(), 1..40),
+        layout in layout(),
+        channel in lossy(),
+        penetration in 0.2..0.8,
+        weather in prop_oneof![Just(Weather::Wet), Just(Weather::Snow)],
+    ) {
+        drains(&schedule, &dimensions(&layout, channel, penetration, weather))?;
+    }
+}
+
+fn schedule(spawns: &[(u64, Airt, u64)]) -> Vec<Spawn> {
+    spawns
+        .iter()
+        .map(|&(after, origin, route)| Spawn {
+            after,
+            origin,
+            route,
+        })
+        .collect()
+}
+
+// Found by hand with a lossy radio in snow: a connected car granted a slot and a person turning across it each waited in the junction for the other, for ever.
+#[test]
+fn granted_car_and_person_do_not_block_each_other() {
+    let schedule = schedule(&[
+        (30, Airt::Left, 7743621180198743495),
+        (231, Airt::Up, 2448223409416711644),
+        (45, Airt::Up, 85673744710599771),
+        (45, Airt::Up, 2454690177220511339),
+        (103, Airt::Down, 14604314100831125514),
+        (88, Airt::Down, 17616587774084750323),
+        (151, Airt::Up, 3887000787896656214),
+    ]);
+    let channel = ChannelConfig {
+        delay: 11,
+        jitter: 3,
+        loss: 0.0359,
+    };
+    let dimensions = dimensions(&Layout::Grid([3, 2, 2, 2]), channel, 0.5, Weather::Snow);
+    drains(&schedule, &dimensions).unwrap();
+}
+
+// Found by the property for mixed traffic in the wet: a person drove on in front of a connected car whose grant was still on its way to it over the radio, and the two ended up side by side on the junction, each turning across the other.
+#[test]
+fn person_is_sensed_before_a_grant_reaches_the_car_they_cross() {
+    let schedule = schedule(&[
+        (30, Airt::Up, 10288098262703581889),
+        (166, Airt::Down, 520387348616944694),
+        (47, Airt::Up, 321001294029182377),
+        (129, Airt::Up, 8080792234948716137),
+        (86, Airt::Down, 16168415782327773608),
+        (110, Airt::Right, 12028754127737144075),
+    ]);
+    let channel = ChannelConfig {
+        delay: 10,
+        jitter: 5,
+        loss: 0.090857628285139,
+    };
+    let dimensions = dimensions(
+        &Layout::Grid([2, 2, 1, 3]),
+        channel,
+        0.7059196301502338,
+        Weather::Wet,
+    );
+    drains(&schedule, &dimensions).unwrap();
+}
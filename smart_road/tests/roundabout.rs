@@ -0,0 +1,57 @@
+// Scenarios on roundabouts that once left cars on the road for good.
+
+use smart_road::{
+    cars::Traffic, geometry::Geometry, invariants::Invariants, network::Network, random,
+    types::Dimensions, world,
+};
+
+// Frames left after the last spawn for every car to get out, the same as the property tests.
+const DRAIN: u64 = 6000;
+
+fn roundabout(circulating: usize) -> Dimensions {
+    let mut dimensions = world::dimensions(860, 860);
+    dimensions.network = Network::grid(1, 1, 860, 860, || {
+        Geometry::roundabout([3, 3, 3, 3], circulating)
+    });
+    dimensions.penetration = 1.0;
+    dimensions
+}
+
+// Spawns a car every `every` frames for `frames` frames, then lets the roads empty, checking every frame.
+fn run(seed: u64, every: u64, frames: u64, dimensions: &Dimensions) -> Traffic {
+    random::seed(seed);
+    let mut traffic = Traffic::new();
+    let mut invariants = Invariants::new(true);
+    invariants.stuck = u64::MAX;
+    traffic.invariants = Some(invariants);
+    for frame in 0..frames {
+        if frame % every == 0 {
+            traffic.push_random(dimensions);
+        }
+        traffic.update(dimensions);
+    }
+    for _ in 0..DRAIN {
+        if traffic.cars.is_empty() {
+            break;
+        }
+        traffic.update(dimensions);
+    }
+    traffic
+}
+
+// On a one-lane ring under light load, a car stopped at the give-way line with its footprint on the ring, giving way to a car going round that was waiting for it to move.
+#[test]
+fn light_load_on_one_lane_ring_drains() {
+    let dimensions = roundabout(1);
+    for seed in 0..30 {
+        let traffic = run(seed, 125, 4000, &dimensions);
+        assert!(
+            traffic.cars.is_empty(),
+            "Seed {}: {} cars still on the road {} frames after the last spawn\n{}",
+            seed,
+            traffic.cars.len(),
+            DRAIN,
+            traffic.dump()
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use sdl2::rect::Rect;
+
+use smart_road::types::Dimensions;
+
+// Maps world coordinates onto the window. The world can be much larger than the window when the network has several junctions.
+pub struct Camera {
+    pub scale: f64,
+    // The world position shown at the top-left corner of the window.
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Camera {
+    // Shows the whole world, centred in the window.
+    pub fn fit(dimensions: &Dimensions) -> Self {
+        let network = &dimensions.network;
+        let scale = (dimensions.window_width as f64 / network.width as f64)
+            .min(dimensions.window_height as f64 / network.height as f64);
+        Camera {
+            scale,
+            x: (network.width as f64 - dimensions.window_width as f64 / scale) / 2.0,
+            y: (network.height as f64 - dimensions.window_height as f64 / scale) / 2.0,
+        }
+    }
+
+    // Shows the world at its natural size, centred on `centre`.
+    pub fn focus(dimensions: &Dimensions, centre: (i32, i32)) -> Self {
+        Camera {
+            scale: 1.0,
+            x: (centre.0 - dimensions.half_width) as f64,
+            y: (centre.1 - dimensions.half_height) as f64,
+        }
+    }
+
+    pub fn point(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            ((x as f64 - self.x) * self.scale).round() as i32,
+            ((y as f64 - self.y) * self.scale).round() as i32,
+        )
+    }
+
+    pub fn rect(&self, x: i32, y: i32, width: u32, height: u32) -> Rect {
+        let (left, top) = self.point(x, y);
+        Rect::new(
+            left,
+            top,
+            ((width as f64 * self.scale).round() as u32).max(1),
+            ((height as f64 * self.scale).round() as u32).max(1),
+        )
+    }
+}
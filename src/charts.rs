@@ -0,0 +1,111 @@
+use sdl2::{
+    pixels::Color,
+    rect::{Point, Rect},
+    render::Canvas,
+    video::Window,
+};
+
+use smart_road::{
+    metrics::{Series, FRAME},
+    types::Dimensions,
+};
+
+use crate::sim::{draw_hud_label, draw_hud_number};
+
+// Samples shown, three minutes of simulated time at one a second.
+const SHOWN: usize = 180;
+// Samples throughput is worked out over, to smooth out cars leaving in bunches.
+const SMOOTHING: usize = 30;
+const WIDTH: u32 = 300;
+// Of each chart, label included.
+const HEIGHT: u32 = 56;
+const PAD: i32 = 8;
+
+// Rolling charts of throughput, cars on the road, cars queuing and close calls, in a panel in the bottom right corner.
+pub fn draw(canvas: &mut Canvas<Window>, dimensions: &Dimensions, series: &Series) {
+    let samples = &series.samples;
+    let start = samples.len().saturating_sub(SHOWN);
+    let seconds = series.every as f64 * FRAME;
+
+    // Cars through per minute over the samples before each one.
+    let throughput = (start..samples.len())
+        .map(|i| {
+            let from = i.saturating_sub(SMOOTHING);
+            if from == i {
+                return 0.0;
+            }
+            (samples[i].passed - samples[from].passed) as f64 * 60.0 / ((i - from) as f64 * seconds)
+        })
+        .collect();
+    let active = samples[start..]
+        .iter()
+        .map(|sample| sample.active as f64)
+        .collect();
+    let queued = samples[start..]
+        .iter()
+        .map(|sample| sample.queues.iter().flatten().sum::<usize>() as f64)
+        .collect();
+    // Close calls since the sample before.
+    let close_calls = (start..samples.len())
+        .map(|i| match i {
+            0 => samples[i].close_calls as f64,
+            _ => (samples[i].close_calls - samples[i - 1].close_calls) as f64,
+        })
+        .collect();
+
+    let charts: [(&str, Color, Vec<f64>); 4] = [
+        ("THROUGHPUT", Color::RGB(60, 220, 60), throughput),
+        ("ACTIVE", Color::RGB(220, 60, 60), active),
+        ("QUEUED", Color::RGB(220, 220, 60), queued),
+        ("CLOSE CALLS", Color::RGB(255, 140, 0), close_calls),
+    ];
+
+    let height = charts.len() as u32 * HEIGHT + 2 * PAD as u32;
+    let left = dimensions.window_width - WIDTH as i32 - 12;
+    let top = dimensions.window_height - height as i32 - 12;
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 180));
+    canvas.fill_rect(Rect::new(left, top, WIDTH, height)).ok();
+    canvas.set_draw_color(Color::RGBA(100, 180, 255, 200));
+    canvas.draw_rect(Rect::new(left, top, WIDTH, height)).ok();
+
+    let plot_width = WIDTH as i32 - 2 * PAD;
+    for (row, (label, color, values)) in charts.iter().enumerate() {
+        let y = top + PAD + row as i32 * HEIGHT as i32;
+        draw_hud_label(canvas, left + PAD, y, label, Color::RGB(255, 255, 255));
+        let latest = values.last().copied().unwrap_or(0.0);
+        draw_hud_number(
+            canvas,
+            left + WIDTH as i32 - PAD - 40,
+            y + 1,
+            latest.round() as i32,
+            *color,
+        );
+
+        // The plot fills the chart below the label, scaled to the highest value shown and filling from the left as samples come in.
+        let (plot_top, plot_height) = (y + 12, HEIGHT as i32 - 18);
+        canvas.set_draw_color(Color::RGB(40, 40, 40));
+        canvas
+            .fill_rect(Rect::new(
+                left + PAD,
+                plot_top,
+                plot_width as u32,
+                plot_height as u32,
+            ))
+            .ok();
+        let highest = values.iter().copied().fold(1.0, f64::max);
+        let points: Vec<Point> = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                Point::new(
+                    left + PAD + i as i32 * plot_width / SHOWN as i32,
+                    plot_top + plot_height
+                        - 1
+                        - (value / highest * (plot_height - 1) as f64).round() as i32,
+                )
+            })
+            .collect();
+        canvas.set_draw_color(*color);
+        canvas.draw_lines(points.as_slice()).ok();
+    }
+}
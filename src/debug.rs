@@ -0,0 +1,107 @@
+use sdl2::{
+    pixels::Color,
+    rect::Point,
+    render::{BlendMode, Canvas},
+    video::Window,
+};
+
+use smart_road::{
+    cars::{Traffic, Wait},
+    types::Dimensions,
+};
+
+use crate::{camera::Camera, sim::draw_hud_number};
+
+// The colour a car is outlined in for what held it up on the last frame, if anything.
+fn colour(wait: Option<Wait>) -> Color {
+    match wait {
+        None => Color::RGB(60, 220, 60),
+        Some(Wait::LaneChange) => Color::RGB(60, 200, 255),
+        Some(Wait::Junction) => Color::RGB(255, 220, 0),
+        Some(Wait::Yielding(_)) => Color::RGB(255, 140, 0),
+        Some(Wait::Behind(_)) => Color::RGB(255, 50, 50),
+    }
+}
+
+// Shows why cars are doing what they do: the cells each junction has kept for this frame, and for each car its footprint as collisions are checked, outlined in the colour of what held it up, its id over its speed and the speed it is heading for, the rest of its way to the exit, and a line to the car it is waiting for.
+pub fn draw(
+    canvas: &mut Canvas<Window>,
+    dimensions: &Dimensions,
+    camera: &Camera,
+    traffic: &Traffic,
+) {
+    let lane_width = dimensions.lane_width;
+    let half = lane_width / 2;
+    let blend_mode = canvas.blend_mode();
+    canvas.set_blend_mode(BlendMode::Blend);
+
+    canvas.set_draw_color(Color::RGBA(80, 140, 255, 90));
+    for (intersection, junction) in traffic
+        .intersections
+        .iter()
+        .zip(&dimensions.network.junctions)
+    {
+        for ((column, row), _) in intersection.kept(traffic.frame) {
+            let (x, y) = (
+                junction.centre.0 + column * lane_width,
+                junction.centre.1 + row * lane_width,
+            );
+            canvas
+                .fill_rect(camera.rect(x, y, lane_width as u32, lane_width as u32))
+                .ok();
+        }
+    }
+
+    for car in &traffic.cars {
+        let (x, y) = camera.point(car.x + half, car.y + half);
+
+        let steps = (car.path.length() - car.travelled).max(0.0) as i32 / lane_width + 1;
+        let way: Vec<Point> = (0..=steps)
+            .map(|step| {
+                let (px, py) = car
+                    .path
+                    .position((car.travelled + (step * lane_width) as f64).min(car.path.length()));
+                let (px, py) = camera.point(px.round() as i32 + half, py.round() as i32 + half);
+                Point::new(px, py)
+            })
+            .collect();
+        canvas.set_draw_color(Color::RGBA(255, 255, 255, 70));
+        canvas.draw_lines(way.as_slice()).ok();
+
+        let colour = colour(car.wait);
+        if let Some(Wait::Yielding(other) | Wait::Behind(other)) = car.wait {
+            if let Some(other) = traffic.cars.iter().find(|candidate| candidate.id == other) {
+                canvas.set_draw_color(colour);
+                canvas
+                    .draw_line(
+                        Point::new(x, y),
+                        camera.point(other.x + half, other.y + half),
+                    )
+                    .ok();
+            }
+        }
+
+        canvas.set_draw_color(colour);
+        canvas
+            .draw_rect(camera.rect(car.x, car.y, lane_width as u32, lane_width as u32))
+            .ok();
+        let (left, top) = camera.point(car.x, car.y);
+        draw_hud_number(
+            canvas,
+            left,
+            top - 20,
+            car.id as i32,
+            Color::RGB(255, 255, 255),
+        );
+        draw_hud_number(canvas, left, top - 10, car.speed, colour);
+        draw_hud_number(
+            canvas,
+            left + 12,
+            top - 10,
+            car.target_speed,
+            Color::RGB(160, 160, 160),
+        );
+    }
+
+    canvas.set_blend_mode(blend_mode);
+}
@@ -0,0 +1,137 @@
+use sdl2::{
+    pixels::Color,
+    rect::Point,
+    render::{Canvas, Texture},
+    video::Window,
+};
+
+use smart_road::{
+    cars::{Car, Traffic},
+    types::Dimensions,
+};
+
+use crate::camera::Camera;
+
+// Drawing the model in the window.
+pub trait Draw {
+    fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        dimensions: &Dimensions,
+        camera: &Camera,
+        car_textures: &[Texture; 4],
+    );
+}
+
+impl Draw for Traffic {
+    fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        dimensions: &Dimensions,
+        camera: &Camera,
+        car_textures: &[Texture; 4],
+    ) {
+        for car in &self.cars {
+            car.draw(canvas, dimensions, camera, car_textures);
+        }
+    }
+}
+
+impl Draw for Car {
+    fn draw(
+        &self,
+        canvas: &mut Canvas<Window>,
+        dimensions: &Dimensions,
+        camera: &Camera,
+        car_textures: &[Texture; 4],
+    ) {
+        if self.x < 0
+            || self.x + dimensions.lane_width > dimensions.network.width
+            || self.y < 0
+            || self.y + dimensions.lane_width > dimensions.network.height
+        {
+            return;
+        }
+
+        let x = self.x;
+        let y = self.y;
+        let lane_width = dimensions.lane_width as u32;
+
+        // Draw speed trail effect behind the car (visual feedback for velocity)
+        let speed_ratio = self.speed as f32 / dimensions.speed.fast as f32;
+        if speed_ratio > 0.3 {
+            // Calculate trail direction (opposite of movement)
+            let trail_length = (speed_ratio * 12.0) as i32;
+            let trail_segments = 3;
+            
+            for i in 0..trail_segments {
+                let alpha = (100.0 * (1.0 - i as f32 / trail_segments as f32) * speed_ratio) as u8;
+                let offset = trail_length * (i + 1) / trail_segments;
+                
+                let heading = self.heading().to_radians();
+                let trail_x = x - (heading.sin() * offset as f64).round() as i32;
+                let trail_y = y + (heading.cos() * offset as f64).round() as i32;
+                
+                // Speed trail color based on car color but semi-transparent
+                let trail_color = match self.color_code {
+                    0 => Color::RGBA(255, 100, 100, alpha),
+                    1 => Color::RGBA(100, 255, 100, alpha),
+                    2 => Color::RGBA(100, 100, 255, alpha),
+                    _ => Color::RGBA(255, 255, 100, alpha),
+                };
+                
+                canvas.set_draw_color(trail_color);
+                let trail_size = (lane_width as f32 * 0.7 * (1.0 - i as f32 / trail_segments as f32)) as u32;
+                canvas
+                    .fill_rect(camera.rect(
+                        trail_x + (lane_width as i32 - trail_size as i32) / 2,
+                        trail_y + (lane_width as i32 - trail_size as i32) / 2,
+                        trail_size,
+                        trail_size,
+                    ))
+                    .ok();
+            }
+        }
+
+        // Use smooth interpolated angle for better turning animation
+        let angle = self.current_angle;
+
+        let destination = camera.rect(x, y, lane_width, lane_width);
+        let center = Point::new(
+            destination.width() as i32 / 2,
+            destination.height() as i32 / 2,
+        );
+
+        let car_texture = &car_textures[self.color_code];
+
+        canvas
+            .copy_ex(
+                car_texture,
+                None, // No cropping (draw the whole texture).
+                Some(destination),
+                angle,
+                Some(center),
+                false,
+                false,
+            )
+            .expect("Failed to draw car with rotation");
+            
+        // Draw speed indicator glow around fast cars
+        if speed_ratio > 0.8 {
+            let glow_color = Color::RGBA(255, 255, 100, 40);
+            canvas.set_draw_color(glow_color);
+            let glow_size = lane_width + 4;
+            canvas
+                .draw_rect(camera.rect(x - 2, y - 2, glow_size, glow_size))
+                .ok();
+        }
+
+        // Outline cars driven by people, to tell them from connected ones
+        if !self.connected {
+            canvas.set_draw_color(Color::RGB(40, 40, 40));
+            canvas
+                .draw_rect(camera.rect(x, y, lane_width, lane_width))
+                .ok();
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use image::{ImageResult, Rgba, RgbaImage};
+use sdl2::{
+    pixels::Color,
+    render::{BlendMode, Canvas},
+    video::Window,
+};
+
+use smart_road::heatmap::Heatmap;
+
+use crate::camera::Camera;
+
+// The colours of a cell: time held up from yellow to red against the worst cell, and close calls in magenta, drawn as a smaller square in the middle so both show. Square roots keep cells that were held up a little from fading out next to the long queues.
+fn colours(
+    stopped: u32,
+    worst_stopped: u32,
+    close_calls: u32,
+    worst_close_calls: u32,
+) -> (Option<[u8; 4]>, Option<[u8; 4]>) {
+    let stopped = (stopped > 0).then(|| {
+        let share = (stopped as f64 / worst_stopped as f64).sqrt();
+        [
+            255,
+            (255.0 * (1.0 - share)) as u8,
+            0,
+            (60.0 + 160.0 * share) as u8,
+        ]
+    });
+    let close_calls = (close_calls > 0).then(|| {
+        let share = (close_calls as f64 / worst_close_calls as f64).sqrt();
+        [255, 0, 255, (100.0 + 155.0 * share) as u8]
+    });
+    (stopped, close_calls)
+}
+
+// Where each coloured square of a cell goes, in world coordinates: the whole cell, or its middle half for close calls, which comes after so that it goes on top.
+fn squares(heatmap: &Heatmap) -> Vec<(i32, i32, i32, [u8; 4])> {
+    let cell = heatmap.cell;
+    let worst = |counts: &[u32]| counts.iter().copied().max().unwrap_or(0);
+    let (worst_stopped, worst_close_calls) = (worst(&heatmap.stopped), worst(&heatmap.close_calls));
+    let mut squares = Vec::new();
+    for row in 0..heatmap.rows {
+        for column in 0..heatmap.columns {
+            let (x, y) = (column as i32 * cell, row as i32 * cell);
+            let index = row * heatmap.columns + column;
+            let (stopped, close_calls) = colours(
+                heatmap.stopped[index],
+                worst_stopped,
+                heatmap.close_calls[index],
+                worst_close_calls,
+            );
+            if let Some(colour) = stopped {
+                squares.push((x, y, cell, colour));
+            }
+            if let Some(colour) = close_calls {
+                squares.push((x + cell / 4, y + cell / 4, cell / 2, colour));
+            }
+        }
+    }
+    squares
+}
+
+// Draws the heatmap over the roads.
+pub fn draw(canvas: &mut Canvas<Window>, camera: &Camera, heatmap: &Heatmap) {
+    let blend_mode = canvas.blend_mode();
+    canvas.set_blend_mode(BlendMode::Blend);
+    for (x, y, size, [r, g, b, a]) in squares(heatmap) {
+        canvas.set_draw_color(Color::RGBA(r, g, b, a));
+        canvas
+            .fill_rect(camera.rect(x, y, size as u32, size as u32))
+            .ok();
+    }
+    canvas.set_blend_mode(blend_mode);
+}
+
+// Writes the heatmap as a PNG the size of the network, see-through where nothing happened, so it can be laid over a screenshot.
+pub fn save(heatmap: &Heatmap, path: &str) -> ImageResult<()> {
+    let cell = heatmap.cell as u32;
+    let mut image = RgbaImage::new(heatmap.columns as u32 * cell, heatmap.rows as u32 * cell);
+    for (x, y, size, colour) in squares(heatmap) {
+        for dy in 0..size as u32 {
+            for dx in 0..size as u32 {
+                image.put_pixel(x as u32 + dx, y as u32 + dy, Rgba(colour));
+            }
+        }
+    }
+    image.save(path)
+}
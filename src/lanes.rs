@@ -6,7 +6,11 @@ use sdl2::{
     video::WindowContext,
 };
 
-use crate::types::Dimensions;
+use smart_road::{
+    geometry::Kind,
+    network::Junction,
+    types::{Airt, Dimensions},
+};
 
 pub fn draw<'a>(
     canvas: &mut sdl2::render::Canvas<sdl2::video::Window>,
@@ -16,309 +20,325 @@ pub fn draw<'a>(
     let mut texture = texture_creator
         .create_texture_target(
             Some(PixelFormatEnum::RGBA8888),
-            dimensions.window_width as u32,
-            dimensions.window_height as u32,
+            dimensions.network.width as u32,
+            dimensions.network.height as u32,
         )
         .expect("Failed to create texture target");
 
     texture.set_blend_mode(BlendMode::Blend);
 
+    let arms = arms(dimensions);
+
     canvas
         .with_texture_canvas(&mut texture, |texture_canvas| {
-            texture_canvas.set_draw_color(Color::RGB(64, 64, 64));
-            texture_canvas
-                .fill_rect(sdl2::rect::Rect::new(
-                    dimensions.half_width - 3 * dimensions.lane_width,
-                    0,
-                    6 * dimensions.lane_width as u32,
-                    dimensions.window_height as u32,
-                ))
-                .unwrap();
-
-            texture_canvas
-                .fill_rect(sdl2::rect::Rect::new(
-                    0,
-                    dimensions.half_height - 3 * dimensions.lane_width,
-                    dimensions.window_width as u32,
-                    6 * dimensions.lane_width as u32,
-                ))
-                .unwrap();
+            let (r, g, b) = dimensions.weather.asphalt();
+            texture_canvas.set_draw_color(Color::RGB(r, g, b));
+            for junction in &dimensions.network.junctions {
+                if junction.geometry.kind == Kind::Crossroad {
+                    let (top, bottom, left, right) =
+                        junction.geometry.bounds(dimensions.lane_width);
+                    texture_canvas
+                        .fill_rect(sdl2::rect::Rect::new(
+                            junction.centre.0 + left,
+                            junction.centre.1 + top,
+                            (right - left) as u32,
+                            (bottom - top) as u32,
+                        ))
+                        .unwrap();
+                }
+            }
+            for arm in &arms {
+                fill_arm_to_texture(texture_canvas, arm, dimensions);
+            }
 
             texture_canvas.set_draw_color(Color::RGB(255, 255, 255));
-            draw_center_lines_to_texture(texture_canvas, dimensions);
-            draw_edge_lines_to_texture(texture_canvas, dimensions);
-            draw_lane_lines_to_texture(texture_canvas, dimensions);
-            draw_give_way_lines_to_texture(texture_canvas, dimensions);
+            for arm in &arms {
+                draw_center_line_to_texture(texture_canvas, arm);
+                draw_edge_lines_to_texture(texture_canvas, arm, dimensions);
+                draw_lane_lines_to_texture(texture_canvas, arm, dimensions);
+                draw_give_way_lines_to_texture(texture_canvas, arm, dimensions);
+            }
+
+            for junction in &dimensions.network.junctions {
+                if junction.geometry.kind == Kind::Crossroad {
+                    draw_closed_sides_to_texture(texture_canvas, junction, &arms, dimensions);
+                }
+            }
+
+            for junction in &dimensions.network.junctions {
+                if let Kind::Roundabout {
+                    island,
+                    circulating,
+                } = junction.geometry.kind
+                {
+                    draw_roundabout_to_texture(
+                        texture_canvas,
+                        junction.centre,
+                        island as i32,
+                        circulating as i32,
+                        &arms,
+                        dimensions,
+                    );
+                }
+            }
         })
         .expect("Failed to render everything on texture");
 
     texture
 }
 
-fn draw_edge_lines_to_texture(texture_canvas: &mut Canvas<Window>, dimensions: &Dimensions) {
-    texture_canvas
-        .draw_line(
-            (dimensions.half_width - 3 * dimensions.lane_width, 0),
-            (
-                dimensions.half_width - 3 * dimensions.lane_width,
-                dimensions.half_height - 3 * dimensions.lane_width,
-            ),
-        )
-        .unwrap();
-    texture_canvas
-        .draw_line(
-            (
-                dimensions.half_width - 3 * dimensions.lane_width,
-                dimensions.half_height + 3 * dimensions.lane_width,
-            ),
-            (
-                dimensions.half_width - 3 * dimensions.lane_width,
-                dimensions.window_height,
-            ),
-        )
-        .unwrap();
+// One road leading away from a junction box, up to the middle of the link to the next junction or to the edge of the world. Traffic on the arm heads `inbound` towards the junction on one side of the centre line and away from it on the other.
+struct Arm {
+    centre: (i32, i32),
+    inbound: Airt,
+    inbound_lanes: i32,
+    outbound_lanes: i32,
+    // Where the arm meets the junction box and how far it runs from there, in pixels.
+    start: i32,
+    length: i32,
+}
 
-    texture_canvas
-        .draw_line(
-            (0, dimensions.half_height - 3 * dimensions.lane_width),
-            (
-                dimensions.half_width - 3 * dimensions.lane_width,
-                dimensions.half_height - 3 * dimensions.lane_width,
-            ),
-        )
-        .unwrap();
-    texture_canvas
-        .draw_line(
-            (
-                dimensions.half_width + 3 * dimensions.lane_width,
-                dimensions.half_height - 3 * dimensions.lane_width,
-            ),
-            (
-                dimensions.window_width,
-                dimensions.half_height - 3 * dimensions.lane_width,
-            ),
-        )
-        .unwrap();
+fn arms(dimensions: &Dimensions) -> Vec<Arm> {
+    let network = &dimensions.network;
+    let mut arms = Vec::new();
 
-    texture_canvas
-        .draw_line(
-            (dimensions.half_width + 3 * dimensions.lane_width, 0),
-            (
-                dimensions.half_width + 3 * dimensions.lane_width,
-                dimensions.half_height - 3 * dimensions.lane_width,
-            ),
-        )
-        .unwrap();
-    texture_canvas
-        .draw_line(
-            (
-                dimensions.half_width + 3 * dimensions.lane_width,
-                dimensions.half_height + 3 * dimensions.lane_width,
-            ),
-            (
-                dimensions.half_width + 3 * dimensions.lane_width,
-                dimensions.window_height,
-            ),
-        )
-        .unwrap();
+    for junction in &network.junctions {
+        let geometry = &junction.geometry;
+        let (x, y) = junction.centre;
+
+        for inbound in Airt::ALL {
+            let outbound = inbound.opposite();
+            // How far the road runs from the junction centre before it meets the next junction's arm, or the edge of the world.
+            let end = match (junction.links[outbound.index()], outbound) {
+                (Some(link), _) => network.link_length(link) / 2,
+                (None, Airt::Up) => y,
+                (None, Airt::Down) => network.height - y,
+                (None, Airt::Left) => x,
+                (None, Airt::Right) => network.width - x,
+            };
+            let start = geometry.reach(inbound, dimensions.lane_width);
+            let arm = Arm {
+                centre: junction.centre,
+                inbound,
+                inbound_lanes: geometry.approach(inbound).len() as i32,
+                outbound_lanes: geometry.exit_lanes(outbound) as i32,
+                start,
+                length: end - start,
+            };
+            if arm.inbound_lanes + arm.outbound_lanes > 0 {
+                arms.push(arm);
+            }
+        }
+    }
+
+    arms
+}
+
+// Converts a point on an arm into world coordinates. `along` is the distance from the junction box and `across` the offset from the centre line, positive on the inbound side.
+fn arm_point(arm: &Arm, along: i32, across: i32) -> (i32, i32) {
+    let along = arm.start + along;
+    let (x, y) = arm.centre;
+    match arm.inbound {
+        Airt::Up => (x + across, y + along),
+        Airt::Down => (x - across, y - along),
+        Airt::Left => (x + along, y - across),
+        Airt::Right => (x - along, y + across),
+    }
+}
+
+fn fill_arm_to_texture(texture_canvas: &mut Canvas<Window>, arm: &Arm, dimensions: &Dimensions) {
+    let lane_width = dimensions.lane_width;
+    let (x1, y1) = arm_point(arm, 0, arm.inbound_lanes * lane_width);
+    let (x2, y2) = arm_point(arm, arm.length, -arm.outbound_lanes * lane_width);
 
     texture_canvas
-        .draw_line(
-            (0, dimensions.half_height + 3 * dimensions.lane_width),
-            (
-                dimensions.half_width - 3 * dimensions.lane_width,
-                dimensions.half_height + 3 * dimensions.lane_width,
-            ),
-        )
-        .unwrap();
-    texture_canvas
-        .draw_line(
-            (
-                dimensions.half_width + 3 * dimensions.lane_width,
-                dimensions.half_height + 3 * dimensions.lane_width,
-            ),
-            (
-                dimensions.window_width,
-                dimensions.half_height + 3 * dimensions.lane_width,
-            ),
-        )
+        .fill_rect(sdl2::rect::Rect::new(
+            x1.min(x2),
+            y1.min(y2),
+            (x1 - x2).unsigned_abs(),
+            (y1 - y2).unsigned_abs(),
+        ))
         .unwrap();
 }
 
-fn draw_give_way_lines_to_texture(texture_canvas: &mut Canvas<Window>, dimensions: &Dimensions) {
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width,
-            dimensions.half_height + 3 * dimensions.lane_width,
-        ),
-        (
-            dimensions.half_width + 3 * dimensions.lane_width,
-            dimensions.half_height + 3 * dimensions.lane_width,
-        ),
-        4.0,
-        4.0,
-    );
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width,
-            dimensions.half_height + 3 * dimensions.lane_width + 8,
-        ),
-        (
-            dimensions.half_width + 3 * dimensions.lane_width,
-            dimensions.half_height + 3 * dimensions.lane_width + 8,
-        ),
-        4.0,
-        4.0,
-    );
-
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width,
-            dimensions.half_height - 3 * dimensions.lane_width,
-        ),
-        (
-            dimensions.half_width - 3 * dimensions.lane_width,
-            dimensions.half_height - 3 * dimensions.lane_width,
-        ),
-        4.0,
-        4.0,
-    );
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width,
-            dimensions.half_height - 3 * dimensions.lane_width - 8,
-        ),
-        (
-            dimensions.half_width - 3 * dimensions.lane_width,
-            dimensions.half_height - 3 * dimensions.lane_width - 8,
-        ),
-        4.0,
-        4.0,
-    );
-
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width + 3 * dimensions.lane_width,
-            dimensions.half_height,
-        ),
-        (
-            dimensions.half_width + 3 * dimensions.lane_width,
-            dimensions.half_height - 3 * dimensions.lane_width,
-        ),
-        4.0,
-        4.0,
-    );
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width + 3 * dimensions.lane_width + 8,
-            dimensions.half_height,
-        ),
-        (
-            dimensions.half_width + 3 * dimensions.lane_width + 8,
-            dimensions.half_height - 3 * dimensions.lane_width,
-        ),
-        4.0,
-        4.0,
-    );
-
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width - 3 * dimensions.lane_width,
-            dimensions.half_height,
-        ),
-        (
-            dimensions.half_width - 3 * dimensions.lane_width,
-            dimensions.half_height + 3 * dimensions.lane_width,
-        ),
-        4.0,
-        4.0,
-    );
-    draw_dashed_line_to_texture(
-        texture_canvas,
-        (
-            dimensions.half_width - 3 * dimensions.lane_width - 8,
-            dimensions.half_height,
-        ),
-        (
-            dimensions.half_width - 3 * dimensions.lane_width - 8,
-            dimensions.half_height + 3 * dimensions.lane_width,
-        ),
-        4.0,
-        4.0,
-    );
+fn draw_edge_lines_to_texture(
+    texture_canvas: &mut Canvas<Window>,
+    arm: &Arm,
+    dimensions: &Dimensions,
+) {
+    for across in [
+        arm.inbound_lanes * dimensions.lane_width,
+        -arm.outbound_lanes * dimensions.lane_width,
+    ] {
+        texture_canvas
+            .draw_line(
+                arm_point(arm, 0, across),
+                arm_point(arm, arm.length, across),
+            )
+            .unwrap();
+    }
 }
 
-fn draw_lane_lines_to_texture(
-    texture_canvas: &mut sdl2::render::Canvas<Window>,
+fn draw_give_way_lines_to_texture(
+    texture_canvas: &mut Canvas<Window>,
+    arm: &Arm,
     dimensions: &Dimensions,
 ) {
-    for i in 1..3 {
-        draw_dashed_line_to_texture(
-            texture_canvas,
-            (dimensions.half_width - dimensions.lane_width * i, 0),
-            (
-                dimensions.half_width - dimensions.lane_width * i,
-                dimensions.window_height,
-            ),
-            4.0,
-            4.0,
-        );
+    for along in [0, 8] {
         draw_dashed_line_to_texture(
             texture_canvas,
-            (dimensions.half_width + dimensions.lane_width * i, 0),
-            (
-                dimensions.half_width + dimensions.lane_width * i,
-                dimensions.window_height,
-            ),
+            arm_point(arm, along, 0),
+            arm_point(arm, along, arm.inbound_lanes * dimensions.lane_width),
             4.0,
             4.0,
         );
+    }
+}
+
+fn draw_lane_lines_to_texture(
+    texture_canvas: &mut sdl2::render::Canvas<Window>,
+    arm: &Arm,
+    dimensions: &Dimensions,
+) {
+    let inbound = (1..arm.inbound_lanes).map(|i| i * dimensions.lane_width);
+    let outbound = (1..arm.outbound_lanes).map(|i| -i * dimensions.lane_width);
+    for across in inbound.chain(outbound) {
         draw_dashed_line_to_texture(
             texture_canvas,
-            (0, dimensions.half_height - dimensions.lane_width * i),
-            (
-                dimensions.window_width,
-                dimensions.half_height - dimensions.lane_width * i,
-            ),
+            arm_point(arm, 0, across),
+            arm_point(arm, arm.length, across),
             4.0,
             4.0,
         );
-        draw_dashed_line_to_texture(
+    }
+}
+
+fn draw_center_line_to_texture(texture_canvas: &mut sdl2::render::Canvas<Window>, arm: &Arm) {
+    if arm.inbound_lanes == 0 || arm.outbound_lanes == 0 {
+        // A one-way arm has no centre line.
+        return;
+    }
+    texture_canvas
+        .draw_line(arm_point(arm, 0, 0), arm_point(arm, arm.length, 0))
+        .unwrap();
+}
+
+// A kerb along each side of a crossroad box that has no arm, as on a T-junction.
+fn draw_closed_sides_to_texture(
+    texture_canvas: &mut Canvas<Window>,
+    junction: &Junction,
+    arms: &[Arm],
+    dimensions: &Dimensions,
+) {
+    let (top, bottom, left, right) = junction.geometry.bounds(dimensions.lane_width);
+    let (x, y) = junction.centre;
+
+    for inbound in Airt::ALL {
+        if arms
+            .iter()
+            .any(|arm| arm.centre == junction.centre && arm.inbound == inbound)
+        {
+            continue;
+        }
+        let (start, end) = match inbound {
+            Airt::Up => ((x + left, y + bottom), (x + right, y + bottom)),
+            Airt::Down => ((x + left, y + top), (x + right, y + top)),
+            Airt::Left => ((x + right, y + top), (x + right, y + bottom)),
+            Airt::Right => ((x + left, y + top), (x + left, y + bottom)),
+        };
+        texture_canvas.draw_line(start, end).unwrap();
+    }
+}
+
+// The ring and island of a roundabout, drawn over the ends of its arms.
+fn draw_roundabout_to_texture(
+    texture_canvas: &mut Canvas<Window>,
+    centre: (i32, i32),
+    island: i32,
+    circulating: i32,
+    arms: &[Arm],
+    dimensions: &Dimensions,
+) {
+    let lane_width = dimensions.lane_width;
+    let radius = (island + circulating) * lane_width;
+
+    let (r, g, b) = dimensions.weather.asphalt();
+    texture_canvas.set_draw_color(Color::RGB(r, g, b));
+    fill_circle_to_texture(texture_canvas, centre, radius);
+    texture_canvas.set_draw_color(Color::RGB(70, 120, 60));
+    fill_circle_to_texture(texture_canvas, centre, island * lane_width);
+
+    // Leave the outer edge open where the arms join the ring.
+    let arms: Vec<&Arm> = arms.iter().filter(|arm| arm.centre == centre).collect();
+    let open = |x: i32, y: i32| {
+        arms.iter().any(|arm| {
+            let (along, across) = match arm.inbound {
+                Airt::Up => (y, x),
+                Airt::Down => (-y, -x),
+                Airt::Left => (x, -y),
+                Airt::Right => (-x, y),
+            };
+            along > 0
+                && across < arm.inbound_lanes * lane_width
+                && across > -arm.outbound_lanes * lane_width
+        })
+    };
+
+    texture_canvas.set_draw_color(Color::RGB(255, 255, 255));
+    draw_circle_to_texture(texture_canvas, centre, radius, None, open);
+    draw_circle_to_texture(texture_canvas, centre, island * lane_width, None, |_, _| {
+        false
+    });
+    for ring in 1..circulating {
+        draw_circle_to_texture(
             texture_canvas,
-            (0, dimensions.half_height + dimensions.lane_width * i),
-            (
-                dimensions.window_width,
-                dimensions.half_height + dimensions.lane_width * i,
-            ),
-            4.0,
-            4.0,
+            centre,
+            (island + ring) * lane_width,
+            Some((4.0, 4.0)),
+            |_, _| false,
         );
     }
 }
 
-fn draw_center_lines_to_texture(
-    texture_canvas: &mut sdl2::render::Canvas<Window>,
-    dimensions: &Dimensions,
+fn fill_circle_to_texture(texture_canvas: &mut Canvas<Window>, centre: (i32, i32), radius: i32) {
+    for dy in -radius..=radius {
+        let dx = ((radius * radius - dy * dy) as f64).sqrt() as i32;
+        texture_canvas
+            .draw_line(
+                (centre.0 - dx, centre.1 + dy),
+                (centre.0 + dx, centre.1 + dy),
+            )
+            .unwrap();
+    }
+}
+
+// Draws a circle one pixel at a time, optionally dashed, leaving out the points (relative to the centre) where `skip` says so.
+fn draw_circle_to_texture(
+    texture_canvas: &mut Canvas<Window>,
+    centre: (i32, i32),
+    radius: i32,
+    dashes: Option<(f32, f32)>,
+    skip: impl Fn(i32, i32) -> bool,
 ) {
-    texture_canvas
-        .draw_line(
-            (dimensions.half_width, 0),
-            (dimensions.half_width, dimensions.window_height),
-        )
-        .unwrap();
-    texture_canvas
-        .draw_line(
-            (0, dimensions.half_height),
-            (dimensions.window_width, dimensions.half_height),
-        )
-        .unwrap();
+    let circumference = std::f32::consts::TAU * radius as f32;
+    let mut distance_traveled = 0.0;
+    while distance_traveled < circumference {
+        let drawn = match dashes {
+            None => true,
+            Some((dash_length, gap_length)) => {
+                distance_traveled % (dash_length + gap_length) < dash_length
+            }
+        };
+        let angle = distance_traveled / radius as f32;
+        let x = (radius as f32 * angle.cos()).round() as i32;
+        let y = (radius as f32 * angle.sin()).round() as i32;
+        if drawn && !skip(x, y) {
+            texture_canvas
+                .draw_point((centre.0 + x, centre.1 + y))
+                .unwrap();
+        }
+        distance_traveled += 0.5;
+    }
 }
 
 fn draw_dashed_line_to_texture(
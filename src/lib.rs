@@ -0,0 +1,12 @@
+// The SDL frontend: the window onto the model in `smart_road`, with the camera, the drawing of the roads, cars, charts and overlays, and the stats window shown at the end. The binary in `main.rs` runs it, and the benchmarks time its drawing.
+
+pub mod camera;
+pub mod charts;
+pub mod debug;
+pub mod draw;
+pub mod heat;
+pub mod lanes;
+pub mod sim;
+pub mod stats;
+pub mod textures;
+pub mod trees;
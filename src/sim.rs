@@ -1,5 +1,5 @@
 use std::{
-    thread,
+    fs, thread,
     time::{Duration, Instant},
 };
 
@@ -13,14 +13,20 @@ use sdl2::{
     Sdl,
 };
 
-use crate::{
+use smart_road::{
     cars::Traffic,
-    textures, trees,
-    types::{Airt, Dimensions, Speed},
+    heatmap::Heatmap,
+    types::{Airt, Dimensions},
+    weather::Weather,
+    world,
 };
 
+use crate::{camera::Camera, charts, debug, draw::Draw, heat, lanes, textures, trees};
+
 pub fn simulate(traffic: &mut Traffic) {
     let (sdl_context, mut canvas, mut dimensions) = setup();
+    // The heatmap covers the network, which is only known once the window is.
+    traffic.heatmap = Some(Heatmap::new(&dimensions));
     let texture_creator = canvas.texture_creator();
     let (background_texture, lanes_texture, car_textures, tree_textures) =
         textures::create_textures(&texture_creator, &dimensions, &mut canvas);
@@ -32,7 +38,7 @@ pub fn simulate(traffic: &mut Traffic) {
         traffic,
         &texture_creator,
         &background_texture,
-        &lanes_texture,
+        lanes_texture,
         &car_textures,
         &tree_textures,
     );
@@ -50,24 +56,7 @@ fn setup() -> (sdl2::Sdl, Canvas<Window>, Dimensions) {
     let window_width = screen_height as f32 * 0.8 * hdpi / 133.0;
     let window_height = screen_height as f32 * 0.8 * vdpi / 139.0;
 
-    const LANE_WIDTH: i32 = 16;
-    let speed = Speed {
-        fast: LANE_WIDTH * 3 / 4,
-        default: LANE_WIDTH / 2,
-        slow: LANE_WIDTH / 4,
-    };
-
-    let window_width = window_width as i32;
-    let window_height = window_height as i32;
-
-    let dimensions = Dimensions {
-        window_width,
-        window_height,
-        half_width: window_width / 2,
-        half_height: window_height / 2,
-        lane_width: LANE_WIDTH,
-        speed,
-    };
+    let dimensions = world::dimensions(window_width as i32, window_height as i32);
 
     let window = video_subsystem
         .window(
@@ -91,14 +80,14 @@ fn setup() -> (sdl2::Sdl, Canvas<Window>, Dimensions) {
     (sdl_context, canvas, dimensions)
 }
 
-fn run(
+fn run<'a>(
     sdl_context: &Sdl,
     canvas: &mut Canvas<sdl2::video::Window>,
     dimensions: &mut Dimensions,
     traffic: &mut Traffic,
-    texture_creator: &TextureCreator<WindowContext>,
+    texture_creator: &'a TextureCreator<WindowContext>,
     background_texture: &sdl2::render::Texture,
-    lanes_texture: &Texture,
+    mut lanes_texture: Texture<'a>,
     car_textures: &[sdl2::render::Texture; 4],
     tree_textures: &Vec<(Texture, [f64; 2])>,
 ) {
@@ -107,7 +96,9 @@ fn run(
     let keypress_interval = Duration::from_millis(128); // Change, e.g. from 128 to 32 to see gridlock.
     let mut start_time = Instant::now();
     let mut is_fullscreen = false;
-    let mut show_help = false;
+    let mut overlays = Overlays::default();
+    // `None` shows the whole network, otherwise the camera follows that junction.
+    let mut focus: Option<usize> = None;
 
     'running: loop {
         let now = Instant::now();
@@ -124,17 +115,22 @@ fn run(
         };
 
         traffic.update(&dimensions);
+        let camera = match focus {
+            None => Camera::fit(dimensions),
+            Some(junction) => Camera::focus(dimensions, dimensions.network.junctions[junction].centre),
+        };
         render(
             canvas,
             &dimensions,
+            &camera,
             &traffic,
             background_texture,
             car_textures,
-            lanes_texture,
+            &lanes_texture,
             texture_creator,
             tree_textures,
             fps,
-            show_help,
+            &overlays,
         );
 
         for event in event_pump.poll_iter() {
@@ -183,7 +179,46 @@ fn run(
                                 .unwrap();
                         }
                         Keycode::H => {
-                            show_help = !show_help;
+                            overlays.help = !overlays.help;
+                        }
+                        Keycode::G => {
+                            overlays.charts = !overlays.charts;
+                        }
+                        Keycode::M => {
+                            overlays.heatmap = !overlays.heatmap;
+                        }
+                        Keycode::D => {
+                            overlays.debug = !overlays.debug;
+                        }
+                        Keycode::P => {
+                            if let Some(heatmap) = &traffic.heatmap {
+                                if let Err(error) = heat::save(heatmap, "heatmap.png") {
+                                    eprintln!("Couldn't write heatmap.png: {}", error);
+                                }
+                            }
+                        }
+                        Keycode::W => {
+                            // The roads are redrawn for the new weather.
+                            dimensions.weather = dimensions.weather.next();
+                            lanes_texture = lanes::draw(canvas, dimensions, texture_creator);
+                        }
+                        Keycode::T => {
+                            // Writes the samples so far, for plotting how congestion built up and cleared.
+                            if let Some(series) = &traffic.series {
+                                if let Err(error) = fs::write("timeline.csv", series.csv()) {
+                                    eprintln!("Couldn't write timeline.csv: {}", error);
+                                }
+                            }
+                        }
+                        Keycode::C => {
+                            // Cycle through the junctions, then back to the whole network.
+                            focus = match focus {
+                                None => Some(0),
+                                Some(junction) if junction + 1 < dimensions.network.junctions.len() => {
+                                    Some(junction + 1)
+                                }
+                                Some(_) => None,
+                            };
                         }
                         _ => {}
                     }
@@ -198,9 +233,19 @@ fn run(
     thread::sleep(Duration::from_millis(128));
 }
 
-fn render(
+// What is drawn over the roads and cars, each turned on and off with its own key.
+#[derive(Clone, Copy, Default)]
+pub struct Overlays {
+    pub help: bool,    // H
+    pub charts: bool,  // G
+    pub heatmap: bool, // M
+    pub debug: bool,   // D
+}
+
+pub fn render(
     canvas: &mut Canvas<sdl2::video::Window>,
     dimensions: &Dimensions,
+    camera: &Camera,
     traffic: &Traffic,
     background_texture: &sdl2::render::Texture,
     car_textures: &[sdl2::render::Texture; 4],
@@ -208,26 +253,64 @@ fn render(
     texture_creator: &TextureCreator<WindowContext>,
     tree_textures: &Vec<(Texture, [f64; 2])>,
     fps: f64,
-    show_help: bool,
+    overlays: &Overlays,
 ) {
     canvas.set_draw_color(Color::RGB(240, 240, 240));
     canvas.clear();
 
     canvas.copy(&tree_textures[0].0, None, None).unwrap();
     canvas.copy(background_texture, None, None).unwrap();
-    canvas.copy(lanes_texture, None, None).unwrap();
+    // Wet ground looks darker.
+    if dimensions.weather == Weather::Wet {
+        canvas.set_draw_color(Color::RGBA(10, 15, 30, 90));
+        canvas.fill_rect(None).ok();
+    }
+    canvas
+        .copy(
+            lanes_texture,
+            None,
+            camera.rect(
+                0,
+                0,
+                dimensions.network.width as u32,
+                dimensions.network.height as u32,
+            ),
+        )
+        .unwrap();
 
-    traffic.draw(canvas, &dimensions, car_textures);
+    if overlays.heatmap {
+        if let Some(heatmap) = &traffic.heatmap {
+            heat::draw(canvas, camera, heatmap);
+        }
+    }
 
-    trees::plant(canvas, tree_textures);
+    traffic.draw(canvas, &dimensions, camera, car_textures);
 
-    let snow = textures::create_speckled_texture(
-        texture_creator,
-        dimensions.window_width as u32,
-        dimensions.window_height as u32,
-        canvas,
-    );
-    canvas.copy(&snow, None, None).unwrap();
+    // The trees are placed around a single crossroad filling the window, and would stand on the roads of a larger network.
+    if dimensions.network.junctions.len() == 1 {
+        trees::plant(canvas, tree_textures);
+    }
+
+    match dimensions.weather {
+        Weather::Snow => {
+            let snow = textures::create_speckled_texture(
+                texture_creator,
+                dimensions.window_width as u32,
+                dimensions.window_height as u32,
+                canvas,
+            );
+            canvas.copy(&snow, None, None).unwrap();
+        }
+        Weather::Fog => {
+            canvas.set_draw_color(Color::RGBA(210, 210, 215, 130));
+            canvas.fill_rect(None).ok();
+        }
+        Weather::Dry | Weather::Wet => {}
+    }
+
+    if overlays.debug {
+        debug::draw(canvas, dimensions, camera, traffic);
+    }
 
         // Draw a comprehensive HUD with better visual hierarchy
     let hud_height = 90u32;
@@ -344,12 +427,18 @@ fn render(
     };
     
     let _ = window.set_title(&format!(
-        "Smart Road {} | FPS: {:.0} | Active: {} | Passed: {} | Give ways: {} | Close calls: {} | Press H for help",
-        safety_status, fps, active_cars, traffic.cars_passed, traffic.give_ways, traffic.close_calls
+        "Smart Road {} | FPS: {:.0} | Active: {} | Passed: {} | Give ways: {} | Close calls: {} | Lane changes: {} | No stop: {} | Connected/Human: {}/{} | Weather: {:?} | Press H for help",
+        safety_status, fps, active_cars, traffic.cars_passed, traffic.give_ways, traffic.close_calls, traffic.lane_changes, traffic.passed_without_stopping, traffic.connected.passed, traffic.human.passed, dimensions.weather
     ));
 
+    if overlays.charts {
+        if let Some(series) = &traffic.series {
+            charts::draw(canvas, dimensions, series);
+        }
+    }
+
     // Draw help overlay if enabled
-    if show_help {
+    if overlays.help {
         draw_help_overlay(canvas, dimensions);
     }
 
@@ -360,7 +449,7 @@ fn draw_help_overlay(canvas: &mut Canvas<sdl2::video::Window>, dimensions: &Dime
     // Semi-transparent dark overlay covering most of screen
     canvas.set_draw_color(Color::RGBA(0, 0, 0, 200));
     let help_width = 500u32;
-    let help_height = 440u32;
+    let help_height = 560u32;
     let help_x = (dimensions.window_width as u32 - help_width) / 2;
     let help_y = (dimensions.window_height as u32 - help_height) / 2;
     
@@ -398,8 +487,8 @@ fn draw_help_overlay(canvas: &mut Canvas<sdl2::video::Window>, dimensions: &Dime
     
     let start_x = help_x as i32 + 30;
     let start_y = help_y as i32 + 70;
-    let key_size = 28u32;
-    let row_height = 48;
+    let key_size = 24u32;
+    let row_height = 31;
     let label_offset = 40;
     
     // Arrow keys section with visual car icons and directional indicators
@@ -412,6 +501,13 @@ fn draw_help_overlay(canvas: &mut Canvas<sdl2::video::Window>, dimensions: &Dime
         (5, Color::RGB(100, 200, 200), "F", "Toggle FULLSCREEN mode"),
         (6, Color::RGB(220, 100, 100), "ESC", "Exit and show STATISTICS"),
         (7, Color::RGB(150, 150, 150), "H", "Toggle this HELP panel"),
+        (8, Color::RGB(80, 200, 120), "G", "Toggle the CHARTS panel"),
+        (9, Color::RGB(230, 140, 40), "M", "Toggle the HEATMAP overlay"),
+        (10, Color::RGB(200, 120, 60), "P", "Save the heatmap as a PNG"),
+        (11, Color::RGB(90, 170, 90), "D", "Toggle the DEBUG overlay"),
+        (12, Color::RGB(90, 140, 230), "W", "Change the WEATHER"),
+        (13, Color::RGB(170, 170, 90), "T", "Save the TIMELINE as a CSV"),
+        (14, Color::RGB(140, 110, 200), "C", "Cycle the CAMERA focus"),
     ];
     
     for (idx, color, symbol, _desc) in controls.iter() {
@@ -508,6 +604,53 @@ fn draw_help_overlay(canvas: &mut Canvas<sdl2::video::Window>, dimensions: &Dime
                 canvas.fill_rect(Rect::new(symbol_x + 3, symbol_y - 6, 3, 12)).ok();
                 canvas.fill_rect(Rect::new(symbol_x - 6, symbol_y - 1, 12, 3)).ok();
             }
+            "G" => {
+                // G letter
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y - 6, 10, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y + 3, 10, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x + 2, symbol_y, 3, 6)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 1, symbol_y, 6, 3)).ok();
+            }
+            "M" => {
+                // M letter
+                canvas.fill_rect(Rect::new(symbol_x - 6, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x + 3, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 6, symbol_y - 6, 12, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 1, symbol_y - 6, 2, 8)).ok();
+            }
+            "P" => {
+                // P letter
+                canvas.fill_rect(Rect::new(symbol_x - 4, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 4, symbol_y - 6, 8, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 4, symbol_y - 1, 8, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x + 2, symbol_y - 6, 3, 7)).ok();
+            }
+            "D" => {
+                // D letter
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y - 6, 8, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y + 3, 8, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x + 3, symbol_y - 4, 3, 8)).ok();
+            }
+            "W" => {
+                // W letter
+                canvas.fill_rect(Rect::new(symbol_x - 6, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x + 3, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 6, symbol_y + 3, 12, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 1, symbol_y - 2, 2, 8)).ok();
+            }
+            "T" => {
+                // T letter
+                canvas.fill_rect(Rect::new(symbol_x - 6, symbol_y - 6, 12, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 1, symbol_y - 6, 3, 12)).ok();
+            }
+            "C" => {
+                // C letter
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y - 6, 3, 12)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y - 6, 10, 3)).ok();
+                canvas.fill_rect(Rect::new(symbol_x - 5, symbol_y + 3, 10, 3)).ok();
+            }
             _ => {}
         }
         
@@ -557,6 +700,74 @@ fn draw_help_overlay(canvas: &mut Canvas<sdl2::video::Window>, dimensions: &Dime
                 canvas.fill_rect(Rect::new(icon_x + 4, icon_y, 8, 4)).ok();
                 canvas.fill_rect(Rect::new(icon_x + 8, icon_y + 16, 4, 4)).ok();
             }
+            8 => {
+                // Bars of a chart
+                canvas.set_draw_color(Color::RGB(80, 200, 120));
+                canvas.fill_rect(Rect::new(icon_x, icon_y + 12, 6, 8)).ok();
+                canvas.fill_rect(Rect::new(icon_x + 8, icon_y + 6, 6, 14)).ok();
+                canvas.fill_rect(Rect::new(icon_x + 16, icon_y, 6, 20)).ok();
+            }
+            9 => {
+                // Squares shading from cool to hot
+                let shades = [
+                    Color::RGB(60, 90, 200),
+                    Color::RGB(230, 200, 60),
+                    Color::RGB(230, 140, 40),
+                    Color::RGB(220, 50, 40),
+                ];
+                for (i, shade) in shades.iter().enumerate() {
+                    canvas.set_draw_color(*shade);
+                    canvas
+                        .fill_rect(Rect::new(icon_x + (i as i32 % 2) * 10, icon_y + (i as i32 / 2) * 10, 10, 10))
+                        .ok();
+                }
+            }
+            10 => {
+                // A picture in a frame
+                canvas.set_draw_color(Color::RGB(200, 120, 60));
+                canvas.draw_rect(Rect::new(icon_x, icon_y, 24, 20)).ok();
+                canvas.set_draw_color(Color::RGB(220, 50, 40));
+                canvas.fill_rect(Rect::new(icon_x + 4, icon_y + 4, 8, 6)).ok();
+                canvas.set_draw_color(Color::RGB(60, 90, 200));
+                canvas.fill_rect(Rect::new(icon_x + 12, icon_y + 10, 8, 6)).ok();
+            }
+            11 => {
+                // A car's outline with a line to the car it waits for
+                canvas.set_draw_color(Color::RGB(90, 200, 90));
+                canvas.draw_rect(Rect::new(icon_x, icon_y + 6, 10, 10)).ok();
+                canvas.set_draw_color(Color::RGB(220, 60, 60));
+                canvas.draw_rect(Rect::new(icon_x + 16, icon_y, 10, 10)).ok();
+                canvas.set_draw_color(Color::RGB(255, 255, 255));
+                canvas.draw_line((icon_x + 5, icon_y + 11), (icon_x + 21, icon_y + 5)).ok();
+            }
+            12 => {
+                // Raindrops
+                canvas.set_draw_color(Color::RGB(90, 140, 230));
+                for i in 0..3 {
+                    canvas.fill_rect(Rect::new(icon_x + i * 8, icon_y + (i % 2) * 6, 3, 10)).ok();
+                }
+            }
+            13 => {
+                // A line rising and falling over time
+                canvas.set_draw_color(Color::RGB(170, 170, 90));
+                let points = [(0, 18), (6, 10), (12, 14), (18, 2), (24, 8)];
+                for pair in points.windows(2) {
+                    canvas
+                        .draw_line(
+                            (icon_x + pair[0].0, icon_y + pair[0].1),
+                            (icon_x + pair[1].0, icon_y + pair[1].1),
+                        )
+                        .ok();
+                }
+            }
+            14 => {
+                // A camera
+                canvas.set_draw_color(Color::RGB(140, 110, 200));
+                canvas.fill_rect(Rect::new(icon_x, icon_y + 4, 24, 16)).ok();
+                canvas.fill_rect(Rect::new(icon_x + 4, icon_y, 8, 4)).ok();
+                canvas.set_draw_color(Color::RGB(50, 50, 70));
+                canvas.fill_rect(Rect::new(icon_x + 8, icon_y + 8, 8, 8)).ok();
+            }
             _ => {}
         }
         
@@ -641,6 +852,13 @@ fn draw_simple_text(canvas: &mut Canvas<sdl2::video::Window>, x: i32, y: i32, _s
         "FULLSCREEN",
         "EXIT & STATS",
         "TOGGLE HELP",
+        "TOGGLE CHARTS",
+        "TOGGLE HEATMAP",
+        "SAVE HEATMAP PNG",
+        "TOGGLE DEBUG",
+        "CHANGE WEATHER",
+        "SAVE TIMELINE CSV",
+        "CAMERA FOCUS",
     ];
     
     if idx < descriptions.len() {
@@ -675,7 +893,7 @@ fn draw_simple_text(canvas: &mut Canvas<sdl2::video::Window>, x: i32, y: i32, _s
 }
 
 // Helper function to draw HUD labels with pixel-art style
-fn draw_hud_label(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color: Color) {
+pub fn draw_hud_label(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color: Color) {
     canvas.set_draw_color(color);
     let mut offset_x = 0;
     
@@ -709,6 +927,20 @@ fn draw_hud_label(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color
                 canvas.fill_rect(Rect::new(char_x, y + 4, 3, 1)).ok();
                 canvas.fill_rect(Rect::new(char_x, y + 7, 4, 1)).ok();
             }
+            'G' => {
+                // G shape
+                canvas.fill_rect(Rect::new(char_x, y + 1, 4, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 2, 1, 5)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 7, 4, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 4, 1, 3)).ok();
+                canvas.fill_rect(Rect::new(char_x + 2, y + 4, 1, 1)).ok();
+            }
+            'H' => {
+                // H shape
+                canvas.fill_rect(Rect::new(char_x, y + 1, 1, 7)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 1, 1, 7)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 4, 4, 1)).ok();
+            }
             'I' => {
                 // I shape
                 canvas.fill_rect(Rect::new(char_x, y + 1, 1, 7)).ok();
@@ -718,6 +950,22 @@ fn draw_hud_label(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color
                 canvas.fill_rect(Rect::new(char_x, y + 1, 1, 7)).ok();
                 canvas.fill_rect(Rect::new(char_x, y + 7, 4, 1)).ok();
             }
+            'M' => {
+                // M shape
+                canvas.fill_rect(Rect::new(char_x, y + 1, 1, 7)).ok();
+                canvas.fill_rect(Rect::new(char_x + 4, y + 1, 1, 7)).ok();
+                canvas.fill_rect(Rect::new(char_x + 1, y + 2, 1, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 2, 1, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 2, y + 3, 1, 2)).ok();
+            }
+            'N' => {
+                // N shape
+                canvas.fill_rect(Rect::new(char_x, y + 1, 1, 7)).ok();
+                canvas.fill_rect(Rect::new(char_x + 4, y + 1, 1, 7)).ok();
+                canvas.fill_rect(Rect::new(char_x + 1, y + 2, 1, 2)).ok();
+                canvas.fill_rect(Rect::new(char_x + 2, y + 4, 1, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 5, 1, 2)).ok();
+            }
             'O' => {
                 // O shape
                 canvas.fill_rect(Rect::new(char_x, y + 1, 4, 1)).ok();
@@ -732,6 +980,24 @@ fn draw_hud_label(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color
                 canvas.fill_rect(Rect::new(char_x + 3, y + 2, 1, 2)).ok();
                 canvas.fill_rect(Rect::new(char_x, y + 4, 4, 1)).ok();
             }
+            'Q' => {
+                // Q shape
+                canvas.fill_rect(Rect::new(char_x, y + 1, 4, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 2, 1, 5)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 2, 1, 5)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 7, 4, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 2, y + 6, 1, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 4, y + 8, 1, 1)).ok();
+            }
+            'R' => {
+                // R shape
+                canvas.fill_rect(Rect::new(char_x, y + 1, 1, 7)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 1, 4, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 2, 1, 2)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 4, 4, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 2, y + 5, 1, 1)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 6, 1, 2)).ok();
+            }
             'S' => {
                 // S shape
                 canvas.fill_rect(Rect::new(char_x, y + 1, 4, 1)).ok();
@@ -745,6 +1011,12 @@ fn draw_hud_label(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color
                 canvas.fill_rect(Rect::new(char_x, y + 1, 5, 1)).ok();
                 canvas.fill_rect(Rect::new(char_x + 2, y + 2, 1, 6)).ok();
             }
+            'U' => {
+                // U shape
+                canvas.fill_rect(Rect::new(char_x, y + 1, 1, 6)).ok();
+                canvas.fill_rect(Rect::new(char_x + 3, y + 1, 1, 6)).ok();
+                canvas.fill_rect(Rect::new(char_x, y + 7, 4, 1)).ok();
+            }
             'V' => {
                 // V shape
                 canvas.fill_rect(Rect::new(char_x, y + 1, 1, 5)).ok();
@@ -769,7 +1041,7 @@ fn draw_hud_label(canvas: &mut Canvas<Window>, x: i32, y: i32, text: &str, color
 }
 
 // Helper function to draw numbers in HUD
-fn draw_hud_number(canvas: &mut Canvas<Window>, x: i32, y: i32, number: i32, color: Color) {
+pub fn draw_hud_number(canvas: &mut Canvas<Window>, x: i32, y: i32, number: i32, color: Color) {
     canvas.set_draw_color(color);
     let text = format!("{}", number);
     let mut offset_x = 0;
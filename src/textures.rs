@@ -7,7 +7,9 @@ use sdl2::{
     video::WindowContext,
 };
 
-use crate::{lanes, types::Dimensions};
+use smart_road::types::Dimensions;
+
+use crate::lanes;
 
 pub fn create_textures<'a>(
     texture_creator: &'a TextureCreator<WindowContext>,